@@ -1,3 +1,5 @@
+use crate::journal::JournalSource;
+use crate::widgets::journal::JournalWindow;
 use crate::widgets::system_overview::Overview;
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
@@ -13,6 +15,10 @@ pub struct TemplateApp {
 
     #[serde(skip)]
     overview: Option<Overview>,
+
+    /// Journals opened from files, of other machines.
+    #[serde(skip)]
+    journals: Vec<JournalWindow>,
 }
 
 impl Default for TemplateApp {
@@ -22,6 +28,7 @@ impl Default for TemplateApp {
             label: "Hello World!".to_owned(),
             value: 2.7,
             overview: None, //Promise::spawn_async(widgets::system_overview::Overview::connect()),
+            journals: Vec::new(),
         }
     }
 }
@@ -66,6 +73,12 @@ impl eframe::App for TemplateApp {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    if let Some(source) = open_journal(ui) {
+                        let mut journal = JournalWindow::new(source);
+                        journal.open(None);
+                        self.journals.push(journal);
+                    }
+                    ui.separator();
                     if ui.button("Quit").clicked() {
                         _frame.close();
                     }
@@ -134,6 +147,11 @@ impl eframe::App for TemplateApp {
             }
         });
 
+        self.journals.retain(JournalWindow::is_open);
+        for journal in &mut self.journals {
+            journal.update(ctx);
+        }
+
         if false {
             egui::Window::new("Window").show(ctx, |ui| {
                 ui.label("Windows can be moved by dragging them.");
@@ -144,3 +162,35 @@ impl eframe::App for TemplateApp {
         }
     }
 }
+
+/// The "Open journal" entries of the File menu, returns what was picked.
+fn open_journal(ui: &mut egui::Ui) -> Option<JournalSource> {
+    let mut source = None;
+    ui.menu_button("Open journal…", |ui| {
+        if ui.button("Directory…").clicked() {
+            ui.close_menu();
+            source = rfd::FileDialog::new()
+                .set_title("Open a journal directory")
+                .set_directory("/var/log/journal")
+                .pick_folder()
+                .map(JournalSource::Directory);
+        }
+        if ui.button("Files…").clicked() {
+            ui.close_menu();
+            source = rfd::FileDialog::new()
+                .set_title("Open journal files")
+                .add_filter("Journal files", &["journal", "journal~"])
+                .pick_files()
+                .map(JournalSource::Files);
+        }
+        if ui.button("Exported (export format or JSON)…").clicked() {
+            ui.close_menu();
+            source = rfd::FileDialog::new()
+                .set_title("Import an exported journal")
+                .add_filter("Journal exports", &["export", "json"])
+                .pick_file()
+                .map(JournalSource::Imported);
+        }
+    });
+    source
+}
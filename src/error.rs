@@ -3,14 +3,15 @@ use tokio::sync::mpsc::error::SendError;
 
 use tokio::task::JoinError;
 
-use crate::journal::Entry;
+use crate::journal::Update;
 
 #[derive(Debug)]
 pub enum Error {
     Systemd(systemd::Error),
     Zbus(zbus::Error),
-    MPSC(Box<SendError<Entry>>),
+    MPSC(Box<SendError<Update>>),
     Join(JoinError),
+    Json(serde_json::Error),
     Custom(&'static str),
 }
 
@@ -22,6 +23,7 @@ impl Display for Error {
             Self::Custom(err) => write!(f, "custom: {err}"),
             Self::MPSC(err) => write!(f, "tokio mpsc: {err}"),
             Self::Join(err) => write!(f, "join: {err}"),
+            Self::Json(err) => write!(f, "json: {err}"),
         }
     }
 }
@@ -38,8 +40,8 @@ impl From<zbus::Error> for Error {
     }
 }
 
-impl From<SendError<Entry>> for Error {
-    fn from(value: SendError<Entry>) -> Self {
+impl From<SendError<Update>> for Error {
+    fn from(value: SendError<Update>) -> Self {
         Self::MPSC(Box::new(value))
     }
 }
@@ -50,4 +52,10 @@ impl From<JoinError> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
 impl std::error::Error for Error {}
@@ -0,0 +1,147 @@
+//! Decoding of how a process ended, see systemd.exec(5), "PROCESS EXIT CODES".
+
+use crate::signal;
+
+/// `ExecMainCode` values, as in `siginfo_t.si_code`.
+const CLD_EXITED: i32 = 1;
+const CLD_KILLED: i32 = 2;
+const CLD_DUMPED: i32 = 3;
+
+/// Exit statuses with a meaning defined by LSB, BSD or systemd itself.
+const EXIT_STATUSES: &[(i32, &str, &str)] = &[
+    (0, "SUCCESS", "success"),
+    (1, "FAILURE", "generic failure"),
+    (2, "INVALIDARGUMENT", "invalid or excess arguments"),
+    (3, "NOTIMPLEMENTED", "unimplemented feature"),
+    (4, "NOPERMISSION", "the user has insufficient privileges"),
+    (5, "NOTINSTALLED", "the program is not installed"),
+    (6, "NOTCONFIGURED", "the program is not configured"),
+    (7, "NOTRUNNING", "the program is not running"),
+    (64, "USAGE", "command line usage error"),
+    (65, "DATAERR", "data format error"),
+    (66, "NOINPUT", "cannot open input"),
+    (67, "NOUSER", "addressee unknown"),
+    (68, "NOHOST", "host name unknown"),
+    (69, "UNAVAILABLE", "service unavailable"),
+    (70, "SOFTWARE", "internal software error"),
+    (71, "OSERR", "system error"),
+    (72, "OSFILE", "critical OS file missing"),
+    (73, "CANTCREAT", "can't create (user) output file"),
+    (74, "IOERR", "input/output error"),
+    (75, "TEMPFAIL", "temporary failure"),
+    (76, "PROTOCOL", "remote error in protocol"),
+    (77, "NOPERM", "permission denied"),
+    (78, "CONFIG", "configuration error"),
+    (200, "CHDIR", "changing to the WorkingDirectory= failed"),
+    (201, "NICE", "setting up the Nice= level failed"),
+    (202, "FDS", "closing unwanted file descriptors failed"),
+    (203, "EXEC", "the actual process execution failed"),
+    (204, "MEMORY", "insufficient memory"),
+    (205, "LIMITS", "adjusting resource limits failed"),
+    (206, "OOM_ADJUST", "setting OOMScoreAdjust= failed"),
+    (207, "SIGNAL_MASK", "setting the signal mask failed"),
+    (208, "STDIN", "setting up standard input failed"),
+    (209, "STDOUT", "setting up standard output failed"),
+    (210, "CHROOT", "changing to the RootDirectory= failed"),
+    (211, "IOPRIO", "setting IOSchedulingClass= failed"),
+    (212, "TIMERSLACK", "setting TimerSlackNSec= failed"),
+    (213, "SECUREBITS", "setting SecureBits= failed"),
+    (214, "SETSCHEDULER", "setting CPUSchedulingPolicy= failed"),
+    (215, "CPUAFFINITY", "setting CPUAffinity= failed"),
+    (216, "GROUP", "determining or changing the group failed"),
+    (217, "USER", "determining or changing the user failed"),
+    (218, "CAPABILITIES", "dropping capabilities failed"),
+    (219, "CGROUP", "setting up the control group failed"),
+    (220, "SETSID", "creating a new session failed"),
+    (221, "CONFIRM", "execution was cancelled on the console"),
+    (222, "STDERR", "setting up standard error failed"),
+    (224, "PAM", "the PAM session failed"),
+    (225, "NETWORK", "setting up PrivateNetwork= failed"),
+    (226, "NAMESPACE", "setting up the mount namespace failed"),
+    (227, "NO_NEW_PRIVILEGES", "setting NoNewPrivileges= failed"),
+    (228, "SECCOMP", "setting up the system call filter failed"),
+    (229, "SELINUX_CONTEXT", "setting the SELinux context failed"),
+    (230, "PERSONALITY", "setting Personality= failed"),
+    (
+        231,
+        "APPARMOR_PROFILE",
+        "setting the AppArmor profile failed",
+    ),
+    (
+        232,
+        "ADDRESS_FAMILIES",
+        "setting RestrictAddressFamilies= failed",
+    ),
+    (
+        233,
+        "RUNTIME_DIRECTORY",
+        "setting up the RuntimeDirectory= failed",
+    ),
+    (235, "CHOWN", "changing socket ownership failed"),
+    (236, "SMACK_PROCESS_LABEL", "setting the SMACK label failed"),
+    (237, "KEYRING", "setting up the kernel keyring failed"),
+    (
+        238,
+        "STATE_DIRECTORY",
+        "setting up the StateDirectory= failed",
+    ),
+    (
+        239,
+        "CACHE_DIRECTORY",
+        "setting up the CacheDirectory= failed",
+    ),
+    (
+        240,
+        "LOGS_DIRECTORY",
+        "setting up the LogsDirectory= failed",
+    ),
+    (
+        241,
+        "CONFIGURATION_DIRECTORY",
+        "setting up the ConfigurationDirectory= failed",
+    ),
+    (242, "NUMA_POLICY", "setting NUMAPolicy= failed"),
+    (243, "CREDENTIALS", "setting up the credentials failed"),
+    (245, "BPF", "applying the BPF restrictions failed"),
+];
+
+/// Returns the symbolic name and meaning of an exit status, when it has one.
+pub fn exit_status(status: i32) -> Option<(&'static str, &'static str)> {
+    EXIT_STATUSES
+        .iter()
+        .find(|(number, _, _)| *number == status)
+        .map(|(_, name, meaning)| (*name, *meaning))
+}
+
+/// Describes `ExecMainCode`/`ExecMainStatus`, e.g. "exited with status 203
+/// (EXIT_EXEC: the actual process execution failed)" or "killed by SIGKILL".
+pub fn describe(code: i32, status: i32) -> String {
+    match code {
+        CLD_EXITED => match exit_status(status) {
+            Some((name, meaning)) => {
+                format!("exited with status {status} (EXIT_{name}: {meaning})")
+            }
+            None => format!("exited with status {status}"),
+        },
+        CLD_KILLED => format!("killed by {}", signal::describe(status)),
+        CLD_DUMPED => format!("dumped core on {}", signal::describe(status)),
+        0 => "has not exited".to_owned(),
+        code => format!("ended with code {code}, status {status}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_signals_outside_the_kill_menu() {
+        assert_eq!(describe(CLD_KILLED, libc::SIGSYS), "killed by SIGSYS");
+        assert_eq!(
+            describe(CLD_DUMPED, libc::SIGXCPU),
+            "dumped core on SIGXCPU"
+        );
+        let realtime = libc::SIGRTMIN() + 2;
+        assert_eq!(describe(CLD_KILLED, realtime), "killed by SIGRTMIN+2");
+    }
+}
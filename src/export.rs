@@ -0,0 +1,224 @@
+//! Writes journal entries out the way `journalctl --output` does, for
+//! attaching logs to a ticket or reading them elsewhere.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::journal::{self, Entry, JournalFilter, JournalSource};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `json`, one object per line.
+    Json,
+    /// `export`, see systemd's JOURNAL_EXPORT_FORMATS.
+    Export,
+    Csv,
+    /// `short-iso`
+    ShortIso,
+    /// `short-precise`
+    ShortPrecise,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 5] = [
+        Self::Json,
+        Self::Export,
+        Self::Csv,
+        Self::ShortIso,
+        Self::ShortPrecise,
+    ];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Export => "export",
+            Self::Csv => "csv",
+            Self::ShortIso | Self::ShortPrecise => "log",
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Json => "JSON lines",
+            Self::Export => "Journal export format",
+            Self::Csv => "CSV",
+            Self::ShortIso => "Text (short-iso)",
+            Self::ShortPrecise => "Text (short-precise)",
+        })
+    }
+}
+
+/// Writes every entry that passes `filter` to `path`, returns how many.
+pub fn export(
+    source: &JournalSource,
+    filter: &JournalFilter,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<usize, Error> {
+    let mut out = BufWriter::new(File::create(path)?);
+    if format == ExportFormat::Csv {
+        writeln!(
+            out,
+            "timestamp,priority,hostname,unit,identifier,pid,message"
+        )?;
+    }
+    let count = journal::read_all(source, filter, |entry| {
+        Ok(write_entry(&mut out, format, entry)?)
+    })?;
+    out.flush()?;
+    Ok(count)
+}
+
+fn write_entry(out: &mut impl Write, format: ExportFormat, entry: &Entry) -> io::Result<()> {
+    match format {
+        ExportFormat::Json => {
+            let mut object = serde_json::Map::new();
+            object.insert("__CURSOR".to_owned(), entry.cursor().into());
+            object.insert(
+                "__REALTIME_TIMESTAMP".to_owned(),
+                entry.realtime().to_string().into(),
+            );
+            object.insert(
+                "__MONOTONIC_TIMESTAMP".to_owned(),
+                entry.monotonic().to_string().into(),
+            );
+            for (field, value) in entry.fields() {
+                // An array of the bytes, for what isn't text.
+                let value = if is_json_binary(value) {
+                    value.as_bytes().to_vec().into()
+                } else {
+                    value.as_str().into()
+                };
+                object.insert(field.clone(), value);
+            }
+            serde_json::to_writer(&mut *out, &object)?;
+            writeln!(out)
+        }
+        ExportFormat::Export => {
+            writeln!(out, "__CURSOR={}", entry.cursor())?;
+            writeln!(out, "__REALTIME_TIMESTAMP={}", entry.realtime())?;
+            writeln!(out, "__MONOTONIC_TIMESTAMP={}", entry.monotonic())?;
+            for (field, value) in entry.fields() {
+                if is_export_binary(value) {
+                    // Binary safe: the name, the size as a little endian
+                    // 64 bit integer, then the data as is.
+                    writeln!(out, "{field}")?;
+                    out.write_all(&(value.len() as u64).to_le_bytes())?;
+                    out.write_all(value.as_bytes())?;
+                    writeln!(out)?;
+                } else {
+                    writeln!(out, "{field}={value}")?;
+                }
+            }
+            writeln!(out)
+        }
+        ExportFormat::Csv => {
+            let columns = [
+                entry.timestamp().to_rfc3339(),
+                format!("{:#}", entry.priority()),
+                entry.field("_HOSTNAME").unwrap_or_default().to_owned(),
+                entry.unit().to_owned(),
+                identifier(entry).to_owned(),
+                pid(entry).unwrap_or_default().to_owned(),
+                entry.message().to_owned(),
+            ];
+            let columns: Vec<String> = columns.iter().map(|column| csv_field(column)).collect();
+            writeln!(out, "{}", columns.join(","))
+        }
+        ExportFormat::ShortIso | ExportFormat::ShortPrecise => {
+            let timestamp = if format == ExportFormat::ShortIso {
+                entry.timestamp().format("%Y-%m-%dT%H:%M:%S%z")
+            } else {
+                entry.timestamp().format("%b %d %H:%M:%S%.6f")
+            };
+            write!(
+                out,
+                "{timestamp} {} {}",
+                entry.field("_HOSTNAME").unwrap_or("localhost"),
+                identifier(entry)
+            )?;
+            if let Some(pid) = pid(entry) {
+                write!(out, "[{pid}]")?;
+            }
+            writeln!(out, ": {}", entry.message())
+        }
+    }
+}
+
+/// Who logged the entry, the way journalctl names it.
+fn identifier(entry: &Entry) -> &str {
+    entry
+        .field("SYSLOG_IDENTIFIER")
+        .or_else(|| entry.field("_COMM"))
+        .unwrap_or("unknown")
+}
+
+fn pid(entry: &Entry) -> Option<&str> {
+    entry.field("SYSLOG_PID").or_else(|| entry.field("_PID"))
+}
+
+/// Whether `journalctl --output=export` writes `value` as data rather than
+/// a line: when it has control characters other than tabs, newlines
+/// included. What wasn't UTF-8 was made text already when it was read.
+fn is_export_binary(value: &str) -> bool {
+    value.chars().any(|c| c.is_control() && c != '\t')
+}
+
+/// Whether `journalctl --output=json` writes `value` as an array of bytes
+/// rather than a string: unlike the export format, it keeps newlines.
+fn is_json_binary(value: &str) -> bool {
+    value
+        .chars()
+        .any(|c| c.is_control() && c != '\t' && c != '\n')
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(format: ExportFormat, fields: &[(&str, &str)]) -> Vec<u8> {
+        let record = fields
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string()))
+            .collect();
+        let mut out = Vec::new();
+        write_entry(&mut out, format, &Entry::imported(record, 0)).unwrap();
+        out
+    }
+
+    #[test]
+    fn json_keeps_multi_line_messages_as_strings() {
+        let json = written(
+            ExportFormat::Json,
+            &[("MESSAGE", "panic:\n\tat main"), ("DATA", "a\u{1}b")],
+        );
+        let object: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_slice(&json).unwrap();
+        assert_eq!(object["MESSAGE"], "panic:\n\tat main");
+        assert_eq!(object["DATA"], serde_json::json!([97, 1, 98]));
+    }
+
+    #[test]
+    fn export_writes_multi_line_messages_as_data() {
+        let export = written(ExportFormat::Export, &[("MESSAGE", "two\nlines")]);
+        let mut framed = b"MESSAGE\n".to_vec();
+        framed.extend_from_slice(&9u64.to_le_bytes());
+        framed.extend_from_slice(b"two\nlines\n");
+        assert!(export
+            .windows(framed.len())
+            .any(|window| window == framed.as_slice()));
+    }
+}
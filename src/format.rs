@@ -0,0 +1,461 @@
+//! Shows systemd's raw property values the way `systemctl` does, and parses
+//! what the user types back into them.
+//!
+//! Most numbers systemd hands out are `u64`s in microseconds or bytes, with
+//! `UINT64_MAX` standing for "infinity" on limits and "n/a" on counters.
+
+use chrono::{DateTime, Local, TimeZone};
+
+pub const USEC_PER_SEC: u64 = 1_000_000;
+const USEC_PER_MINUTE: u64 = 60 * USEC_PER_SEC;
+const USEC_PER_HOUR: u64 = 60 * USEC_PER_MINUTE;
+const USEC_PER_DAY: u64 = 24 * USEC_PER_HOUR;
+const TIMESPAN_UNITS: [(&str, u64); 6] = [
+    ("d", USEC_PER_DAY),
+    ("h", USEC_PER_HOUR),
+    ("min", USEC_PER_MINUTE),
+    ("s", USEC_PER_SEC),
+    ("ms", 1000),
+    ("us", 1),
+];
+/// What `parse_timespan` accepts besides [`TIMESPAN_UNITS`], like systemd.time(7).
+const TIMESPAN_ALIASES: [(&str, u64); 19] = [
+    ("usec", 1),
+    ("µs", 1),
+    ("msec", 1000),
+    ("seconds", USEC_PER_SEC),
+    ("second", USEC_PER_SEC),
+    ("sec", USEC_PER_SEC),
+    ("minutes", USEC_PER_MINUTE),
+    ("minute", USEC_PER_MINUTE),
+    ("m", USEC_PER_MINUTE),
+    ("hours", USEC_PER_HOUR),
+    ("hour", USEC_PER_HOUR),
+    ("hr", USEC_PER_HOUR),
+    ("days", USEC_PER_DAY),
+    ("day", USEC_PER_DAY),
+    ("weeks", 7 * USEC_PER_DAY),
+    ("week", 7 * USEC_PER_DAY),
+    ("w", 7 * USEC_PER_DAY),
+    ("years", 31_557_600 * USEC_PER_SEC),
+    ("y", 31_557_600 * USEC_PER_SEC),
+];
+/// Parts of variable names that hint at a value better not shown on screen.
+const SECRET_HINTS: [&str; 9] = [
+    "PASSWORD",
+    "PASSWD",
+    "PASSPHRASE",
+    "SECRET",
+    "TOKEN",
+    "KEY",
+    "CREDENTIAL",
+    "AUTH",
+    "PRIVATE",
+];
+/// Shown instead of the value of a variable that [`looks_secret`].
+pub const SECRET_MASK: &str = "••••••••";
+const IEC_SUFFIXES: [(char, u64); 6] = [
+    ('E', 1 << 60),
+    ('P', 1 << 50),
+    ('T', 1 << 40),
+    ('G', 1 << 30),
+    ('M', 1 << 20),
+    ('K', 1 << 10),
+];
+
+/// Formats a byte size the way systemd does, e.g. `512M` or `1.5G`.
+pub fn bytes(value: u64) -> String {
+    if value == u64::MAX {
+        return "infinity".to_owned();
+    }
+    for (suffix, factor) in IEC_SUFFIXES {
+        if value >= factor {
+            let whole = value / factor;
+            return if whole * factor == value {
+                format!("{whole}{suffix}")
+            } else {
+                format!("{:.1}{suffix}", value as f64 / factor as f64)
+            };
+        }
+    }
+    format!("{value}B")
+}
+
+/// Parses a byte size like `512M`, `1.5G` or `infinity`, using IEC
+/// (base 1024) suffixes like systemd does.
+pub fn parse_bytes(value: &str) -> Result<u64, &'static str> {
+    let value = value.trim();
+    if value.is_empty() || value == "infinity" {
+        return Ok(u64::MAX);
+    }
+    let number_end = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(number_end);
+    let number: f64 = number.parse().map_err(|_| "invalid size")?;
+    let factor = match suffix.trim() {
+        "" | "B" => 1,
+        suffix => {
+            let mut chars = suffix.chars();
+            let unit = chars.next().map(|c| c.to_ascii_uppercase());
+            if !matches!(chars.as_str(), "" | "B" | "iB") {
+                return Err("unknown size suffix");
+            }
+            IEC_SUFFIXES
+                .iter()
+                .find(|(s, _)| Some(*s) == unit)
+                .map(|(_, factor)| *factor)
+                .ok_or("unknown size suffix")?
+        }
+    };
+    let bytes = number * factor as f64;
+    if bytes >= u64::MAX as f64 {
+        return Err("size too large");
+    }
+    Ok(bytes as u64)
+}
+
+/// Formats CPU time per second of wall clock time, in microseconds, as a
+/// percentage like `CPUQuota=`, e.g. `150%`.
+pub fn percent(usec: u64) -> String {
+    if usec == u64::MAX {
+        return "infinity".to_owned();
+    }
+    format!("{}%", usec as f64 * 100.0 / USEC_PER_SEC as f64)
+}
+
+/// Parses a percentage like `50%` or `infinity` into microseconds per
+/// second.
+pub fn parse_percent(value: &str) -> Result<u64, &'static str> {
+    let value = value.trim();
+    if value.is_empty() || value == "infinity" {
+        return Ok(u64::MAX);
+    }
+    let percent: f64 = value
+        .strip_suffix('%')
+        .ok_or("expected a percentage like 50%")?
+        .trim()
+        .parse()
+        .map_err(|_| "invalid percentage")?;
+    if !percent.is_finite() || percent <= 0.0 {
+        return Err("percentage must be positive");
+    }
+    Ok((percent * USEC_PER_SEC as f64 / 100.0).round() as u64)
+}
+
+/// Formats a timespan in microseconds the way systemd does, e.g. `1min 30s`.
+pub fn timespan(usec: u64) -> String {
+    if usec == u64::MAX {
+        return "infinity".to_owned();
+    }
+    let mut rest = usec;
+    let mut parts = Vec::new();
+    for (suffix, factor) in TIMESPAN_UNITS {
+        if rest >= factor {
+            parts.push(format!("{}{suffix}", rest / factor));
+            rest %= factor;
+        }
+    }
+    if parts.is_empty() {
+        "0".to_owned()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Parses a timespan like `1min 30s`, `2.5h` or `infinity`; a bare number
+/// is in seconds, like in unit files.
+pub fn parse_timespan(value: &str) -> Result<u64, &'static str> {
+    let value = value.trim();
+    if value == "infinity" {
+        return Ok(u64::MAX);
+    }
+    if value.is_empty() {
+        return Err("expected a timespan like 1min 30s");
+    }
+    let mut rest = value;
+    let mut total: f64 = 0.0;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_end);
+        let number: f64 = number.parse().map_err(|_| "invalid timespan")?;
+        let tail = tail.trim_start();
+        let unit_end = tail
+            .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+        let factor = match unit {
+            "" => USEC_PER_SEC,
+            unit => TIMESPAN_UNITS
+                .iter()
+                .chain(TIMESPAN_ALIASES.iter())
+                .find(|(suffix, _)| *suffix == unit)
+                .map(|(_, factor)| *factor)
+                .ok_or("unknown time unit")?,
+        };
+        total += number * factor as f64;
+        rest = tail.trim_start();
+    }
+    if total >= u64::MAX as f64 {
+        return Err("timespan too large");
+    }
+    Ok(total as u64)
+}
+
+/// Formats like `systemd-analyze` does, e.g. `1min 2.345s` or `56ms`.
+pub fn duration(usec: u64) -> String {
+    let secs = usec as f64 / USEC_PER_SEC as f64;
+    match usec / USEC_PER_SEC {
+        minutes if minutes >= 60 => format!("{}min {:.3}s", minutes / 60, secs % 60.0),
+        0 => format!("{}ms", usec / 1000),
+        _ => format!("{secs:.3}s"),
+    }
+}
+
+/// How long ago, or from now, `time` is, in the two largest units like
+/// `systemctl status`, e.g. `2h 5min ago`.
+pub fn age(time: DateTime<Local>) -> String {
+    let delta = Local::now().signed_duration_since(time);
+    let (usec, suffix) = match delta.num_microseconds() {
+        Some(usec) if usec >= 0 => (usec as u64, "ago"),
+        Some(usec) => (usec.unsigned_abs(), "left"),
+        None => return String::new(),
+    };
+    let mut rest = usec;
+    let mut parts = Vec::new();
+    for (unit, factor) in &TIMESPAN_UNITS[..4] {
+        if rest >= *factor && parts.len() < 2 {
+            parts.push(format!("{}{unit}", rest / factor));
+            rest %= factor;
+        }
+    }
+    if parts.is_empty() {
+        return "just now".to_owned();
+    }
+    format!("{} {suffix}", parts.join(" "))
+}
+
+/// A local date like `systemctl` prints, e.g. `Sat 2023-07-01 12:00:00 EEST`.
+pub fn date(time: DateTime<Local>) -> String {
+    time.format("%a %Y-%m-%d %T %Z").to_string()
+}
+
+/// A local time like journalctl's short output, e.g. `Jul 01 12:00:00`.
+pub fn short_date(time: DateTime<Local>) -> String {
+    time.format("%b %d %T").to_string()
+}
+
+/// Formats a wall clock timestamp in microseconds since the epoch with its
+/// age, e.g. `Sat 2023-07-01 12:00:00 EEST; 5min ago`, 0 meaning it never
+/// happened.
+pub fn timestamp(usec: u64) -> String {
+    if usec == 0 || usec == u64::MAX {
+        return "n/a".to_owned();
+    }
+    match Local
+        .timestamp_opt((usec / USEC_PER_SEC) as i64, 0)
+        .single()
+    {
+        Some(time) => format!("{}; {}", date(time), age(time)),
+        None => usec.to_string(),
+    }
+}
+
+/// Parses a point in time like journalctl's `--since` does: a local
+/// `2023-07-01 12:00:00` (the time or its seconds may be left out), `now`,
+/// `today`, `yesterday`, or a timespan back from now like `-1h 30min`.
+/// Returns microseconds since the epoch.
+pub fn parse_timestamp(value: &str) -> Result<u64, &'static str> {
+    let value = value.trim();
+    let now = Local::now();
+    let midnight = |days_ago: i64| {
+        (now.date_naive() - chrono::Duration::days(days_ago))
+            .and_hms_opt(0, 0, 0)
+            .and_then(|time| Local.from_local_datetime(&time).single())
+    };
+    let time = match value {
+        "now" => Some(now),
+        "today" => midnight(0),
+        "yesterday" => midnight(1),
+        value => match value.strip_prefix('-') {
+            Some(ago) => {
+                let ago = parse_timespan(ago)?;
+                let now = now.timestamp_micros() as u64;
+                return now.checked_sub(ago).ok_or("too far back");
+            }
+            None => ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+                .iter()
+                .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+                .or_else(|| {
+                    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                        .ok()
+                        .and_then(|date| date.and_hms_opt(0, 0, 0))
+                })
+                .map(|time| Local.from_local_datetime(&time).single())
+                .ok_or("expected a time like 2023-07-01 12:00:00")?,
+        },
+    };
+    time.map(|time| time.timestamp_micros() as u64)
+        .ok_or("ambiguous local time")
+}
+
+pub fn looks_secret(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SECRET_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// An `Environment=` assignment, its value masked if the variable
+/// [`looks_secret`].
+fn environment(assignment: &str) -> String {
+    match assignment.split_once('=') {
+        Some((name, _)) if looks_secret(name) => format!("{name}={SECRET_MASK}"),
+        _ => assignment.to_owned(),
+    }
+}
+
+/// A color as `#rrggbb`, for Graphviz and SVG.
+pub fn hex_color(color: egui::Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Whether the property named `name` counts something up, where
+/// `UINT64_MAX` means it isn't known rather than that there is no limit.
+fn is_counter(name: &str) -> bool {
+    ["Current", "Peak", "NSec", "Bytes", "Packets", "Operations"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+fn is_bytes(name: &str) -> bool {
+    name.ends_with("Bytes")
+        || name.starts_with("Memory")
+        || name.starts_with("DefaultMemory")
+        || name.starts_with("StartupMemory")
+}
+
+/// Decodes a property value into something readable, going by systemd's
+/// naming conventions: `…Timestamp` is a wall clock time, `…Monotonic` the
+/// time since boot, `…USec` and `…NSec` timespans, and `Memory…`/`…Bytes`
+/// sizes.
+pub fn value(name: &str, value: &zvariant::Value<'_>) -> String {
+    use zvariant::Value;
+    match value {
+        Value::U64(u64::MAX) if is_counter(name) => "n/a".to_owned(),
+        Value::U64(usec) if name.ends_with("Timestamp") => timestamp(*usec),
+        Value::U64(0) if name.ends_with("TimestampMonotonic") => "n/a".to_owned(),
+        Value::U64(usec) if name.ends_with("Monotonic") || name.ends_with("USec") => {
+            timespan(*usec)
+        }
+        Value::U64(nsec) if name.ends_with("NSec") => timespan(nsec / 1000),
+        Value::U64(size) if is_bytes(name) => bytes(*size),
+        Value::U64(u64::MAX) => "infinity".to_owned(),
+        Value::U8(v) => v.to_string(),
+        Value::Bool(v) => if *v { "yes" } else { "no" }.to_owned(),
+        Value::I16(v) => v.to_string(),
+        Value::U16(v) => v.to_string(),
+        Value::I32(v) => v.to_string(),
+        Value::U32(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::U64(v) => v.to_string(),
+        Value::F64(v) => v.to_string(),
+        Value::Str(v) if name == "Environment" => environment(v),
+        Value::Str(v) => v.to_string(),
+        Value::Signature(v) => v.to_string(),
+        Value::ObjectPath(v) => v.to_string(),
+        Value::Value(v) => self::value(name, v),
+        // Byte arrays are mostly IDs, like `InvocationID`.
+        Value::Array(array) if array.element_signature() == "y" => array
+            .get()
+            .iter()
+            .map(|byte| match byte {
+                Value::U8(byte) => format!("{byte:02x}"),
+                _ => String::new(),
+            })
+            .collect(),
+        Value::Array(array) => {
+            let elements: Vec<String> = array
+                .get()
+                .iter()
+                .map(|element| self::value(name, element))
+                .collect();
+            format!("[{}]", elements.join(", "))
+        }
+        Value::Structure(structure) => {
+            let fields: Vec<String> = structure
+                .fields()
+                .iter()
+                .map(|field| self::value(name, field))
+                .collect();
+            format!("({})", fields.join(", "))
+        }
+        other => format!("{other:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_round_trip() {
+        for size in [0, 1000, 1 << 10, 512 << 20, 1536 << 20, 3 << 40, u64::MAX] {
+            assert_eq!(parse_bytes(&bytes(size)), Ok(size), "{}", bytes(size));
+        }
+        assert_eq!(parse_bytes("1.5GiB"), Ok(1536 << 20));
+        assert_eq!(parse_bytes("512k"), Ok(512 << 10));
+        assert!(parse_bytes("5X").is_err());
+    }
+
+    #[test]
+    fn timespan_round_trip() {
+        for usec in [
+            0,
+            1,
+            1500,
+            90 * USEC_PER_SEC,
+            USEC_PER_DAY + 2 * USEC_PER_HOUR + 3 * USEC_PER_MINUTE + 4 * USEC_PER_SEC + 5,
+            u64::MAX,
+        ] {
+            assert_eq!(
+                parse_timespan(&timespan(usec)),
+                Ok(usec),
+                "{}",
+                timespan(usec)
+            );
+        }
+        assert_eq!(parse_timespan("2.5h"), Ok(150 * USEC_PER_MINUTE));
+        assert_eq!(parse_timespan("30"), Ok(30 * USEC_PER_SEC));
+        assert_eq!(parse_timespan("1 week"), Ok(7 * USEC_PER_DAY));
+        assert!(parse_timespan("").is_err());
+        assert!(parse_timespan("5 fortnights").is_err());
+    }
+
+    #[test]
+    fn environment_secrets_are_masked() {
+        let value = zvariant::Value::from(vec!["LANG=C", "API_TOKEN=hunter2"]);
+        let shown = self::value("Environment", &value);
+        assert_eq!(shown, format!("[LANG=C, API_TOKEN={SECRET_MASK}]"));
+        assert_eq!(
+            self::value("Description", &"API_TOKEN=x".into()),
+            "API_TOKEN=x"
+        );
+    }
+
+    #[test]
+    fn percent_round_trip() {
+        for usec in [
+            10_000,
+            291_000,
+            USEC_PER_SEC / 2,
+            2 * USEC_PER_SEC,
+            u64::MAX,
+        ] {
+            assert_eq!(parse_percent(&percent(usec)), Ok(usec), "{}", percent(usec));
+        }
+        assert_eq!(parse_percent("29.1%"), Ok(291_000));
+        assert!(parse_percent("50").is_err());
+        assert!(parse_percent("-5%").is_err());
+    }
+}
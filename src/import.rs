@@ -0,0 +1,161 @@
+//! Reads back what `journalctl --output=export` or `--output=json` wrote, for
+//! logs that come from another machine without its journal files.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use serde_json::Value;
+use systemd::journal::JournalRecord;
+
+use crate::error::Error;
+use crate::journal::Entry;
+
+/// Reads the entries of `path`, in the export format or JSON lines,
+/// whichever it starts like.
+pub fn import(path: &Path) -> Result<Vec<Entry>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let json = reader.fill_buf()?.first() == Some(&b'{');
+    let records = if json {
+        json_lines(reader)?
+    } else {
+        export_format(reader)?
+    };
+    Ok(records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| Entry::imported(record, index))
+        .collect())
+}
+
+/// One `FIELD=value` per line and a blank line after each entry; a value
+/// that isn't text comes after a line with just the field name, as a little
+/// endian 64 bit size and that many bytes.
+fn export_format(mut reader: impl BufRead) -> Result<Vec<JournalRecord>, Error> {
+    let mut records = Vec::new();
+    let mut record = JournalRecord::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        if line.is_empty() {
+            if !record.is_empty() {
+                records.push(std::mem::take(&mut record));
+            }
+            continue;
+        }
+        match line.iter().position(|byte| *byte == b'=') {
+            Some(at) => {
+                record.insert(
+                    String::from_utf8_lossy(&line[..at]).into_owned(),
+                    String::from_utf8_lossy(&line[at + 1..]).into_owned(),
+                );
+            }
+            None => {
+                let mut size = [0; 8];
+                reader.read_exact(&mut size)?;
+                let size = u64::from_le_bytes(size);
+                // Read up to the size given, not allocated up front, as a
+                // file that isn't in the format may give any size.
+                let mut data = Vec::new();
+                reader.by_ref().take(size).read_to_end(&mut data)?;
+                if data.len() as u64 != size {
+                    return Err(Error::Custom("the file ends in the middle of a field"));
+                }
+                // The newline after the data.
+                reader.read_exact(&mut [0])?;
+                record.insert(
+                    String::from_utf8_lossy(&line).into_owned(),
+                    String::from_utf8_lossy(&data).into_owned(),
+                );
+            }
+        }
+    }
+    if !record.is_empty() {
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// One object per line, its values strings, arrays of bytes for what isn't
+/// text, or arrays of those for fields given more than once.
+fn json_lines(reader: impl BufRead) -> Result<Vec<JournalRecord>, Error> {
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let object: serde_json::Map<String, Value> = serde_json::from_str(&line)?;
+        let record = object
+            .into_iter()
+            .filter_map(|(field, value)| Some((field, json_value(value)?)))
+            .collect();
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn json_value(value: Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Array(values) if values.iter().all(Value::is_number) => {
+            let bytes: Vec<u8> = values
+                .iter()
+                .filter_map(|byte| byte.as_u64().map(|byte| byte as u8))
+                .collect();
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        // The first of the values given for the field.
+        Value::Array(values) => values.into_iter().find_map(json_value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Priority;
+
+    #[test]
+    fn export_format_reads_binary_fields() {
+        let mut file = b"__CURSOR=s=1\nMESSAGE\n".to_vec();
+        file.extend_from_slice(&10u64.to_le_bytes());
+        file.extend_from_slice(b"two\n\tlines\nPRIORITY=6\n\n__CURSOR=s=2\n");
+        let records = export_format(file.as_slice()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["MESSAGE"], "two\n\tlines");
+        assert_eq!(records[0]["PRIORITY"], "6");
+        assert_eq!(records[1]["__CURSOR"], "s=2");
+    }
+
+    #[test]
+    fn export_format_rejects_sizes_past_the_end() {
+        let mut file = b"MESSAGE\n".to_vec();
+        file.extend_from_slice(&u64::MAX.to_le_bytes());
+        file.extend_from_slice(b"short\n");
+        assert!(export_format(file.as_slice()).is_err());
+    }
+
+    #[test]
+    fn invalid_priorities_fall_back_to_the_default() {
+        let file =
+            b"MESSAGE=a\nPRIORITY=warning\n\nMESSAGE=b\nPRIORITY=\n\nMESSAGE=c\nPRIORITY=4\n";
+        let priorities: Vec<_> = export_format(file.as_slice())
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .map(|(index, record)| Entry::imported(record, index).priority())
+            .collect();
+        assert_eq!(
+            priorities,
+            [Priority::default(), Priority::default(), Priority::Warning]
+        );
+    }
+}
@@ -1,20 +1,28 @@
-
 use crate::error::Error;
+use crate::import;
 use chrono::{DateTime, Local};
+use foreign_types::ForeignType;
 use poll_promise::Promise;
-use std::collections::btree_map::Iter;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
 use std::default::Default;
+use std::ffi::CString;
 use std::fmt::Display;
-use std::ops::Index;
-use std::slice::SliceIndex;
-use std::time::{Duration, SystemTime};
-use systemd::journal::OpenOptions;
-use tokio::sync::mpsc::{error::TryRecvError, Receiver, Sender};
+use std::ops::{Index, Range};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use systemd::journal::{
+    Journal, JournalRecord, OpenDirectoryOptions, OpenFilesOptions, OpenOptions,
+};
+use tokio::sync::mpsc::{
+    error::TryRecvError, Receiver, Sender, UnboundedReceiver, UnboundedSender,
+};
 use tokio::task::JoinHandle;
 
 /// Use the simple formatting for full names or the alternative one for
 /// syslog-like names.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub enum Priority {
     Emergency,
     Alert,
@@ -26,34 +34,49 @@ pub enum Priority {
     Debug,
 }
 
+impl Priority {
+    pub const ALL: [Self; 8] = [
+        Self::Emergency,
+        Self::Alert,
+        Self::Critical,
+        Self::Error,
+        Self::Warning,
+        Self::Notice,
+        Self::Info,
+        Self::Debug,
+    ];
+
+    /// The syslog(3) level, from 0 for emergency to 7 for debug.
+    pub fn level(self) -> u8 {
+        self as u8
+    }
+}
+
 impl Default for Priority {
     fn default() -> Self {
         Self::Info
     }
 }
 
-impl From<&str> for Priority {
-    fn from(value: &str) -> Self {
+/// Parses a `PRIORITY` field, which an imported file may hold anything in.
+impl TryFrom<&str> for Priority {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, ()> {
         match value {
-            "0" => Self::Emergency,
-            "1" => Self::Alert,
-            "2" => Self::Critical,
-            "3" => Self::Error,
-            "4" => Self::Warning,
-            "5" => Self::Notice,
-            "6" => Self::Info,
-            "7" => Self::Debug,
-            _ => panic!("invalid priority {value}"),
+            "0" => Ok(Self::Emergency),
+            "1" => Ok(Self::Alert),
+            "2" => Ok(Self::Critical),
+            "3" => Ok(Self::Error),
+            "4" => Ok(Self::Warning),
+            "5" => Ok(Self::Notice),
+            "6" => Ok(Self::Info),
+            "7" => Ok(Self::Debug),
+            _ => Err(()),
         }
     }
 }
 
-impl From<String> for Priority {
-    fn from(value: String) -> Self {
-        Self::from(value.as_str())
-    }
-}
-
 impl Display for Priority {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if f.alternate() {
@@ -82,206 +105,1162 @@ impl Display for Priority {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EntryCommon {
-    /// field _SYSTEMD_SLICE=
-    pub slice: String,
-    /// field _SYSTEMD_UNIT=
-    pub systemd_unit: String,
-    /// field _COMM=
-    pub comm: String,
-    /// field SYSLOG_IDENTIFIER=
-    pub syslog_id: String,
     /// When this entry was received by the journal.
     pub timestamp: SystemTime,
     /// field MESSAGE=
     pub message: String,
-    /// field UNIT= for systemd entries
-    pub unit: String,
     /// field PRIORITY=, see levels on syslog(3)
     pub priority: Priority,
+    /// Where the entry is in the journal, see sd_journal_get_cursor(3).
+    pub cursor: String,
+    /// Microseconds since the boot, see sd_journal_get_monotonic_usec(3).
+    pub monotonic: u64,
+    /// Every field of the entry, including the ones above, see
+    /// systemd.journal-fields(7).
+    pub fields: JournalRecord,
 }
 
-impl EntryCommon {
-    fn with_timestamp(timestamp: SystemTime) -> Self {
-        Self {
-            timestamp,
-            slice: Default::default(),
-            systemd_unit: Default::default(),
-            comm: Default::default(),
-            syslog_id: Default::default(),
-            message: Default::default(),
-            unit: Default::default(),
-            priority: Default::default(),
-        }
-    }
-}
-
-#[derive(Debug)]
+/// Where an entry comes from, by how it reached the journal, see `_TRANSPORT=`
+/// in systemd.journal-fields(7).
+#[derive(Debug, Clone)]
 pub enum Entry {
+    /// The kernel log buffer.
+    Kernel(EntryCommon),
+    /// The kernel audit subsystem.
+    Audit(EntryCommon),
+    /// journald itself.
+    Driver(EntryCommon),
+    /// The system manager, PID 1, mostly about units.
     System(EntryCommon),
-    Service(EntryCommon),
+    /// A `systemd --user` instance, about the user's units.
+    UserManager(EntryCommon),
+    /// syslog(3).
+    Syslog(EntryCommon),
+    /// sd_journal_print(3) and the like.
+    Journal(EntryCommon),
+    /// The standard output or error of a service.
+    Stdout(EntryCommon),
 }
 
 impl Entry {
-    pub fn is_for_unit(&self, s: &str) -> bool {
+    fn common(&self) -> &EntryCommon {
         match self {
-            Self::System(entry) => entry.unit == s,
-            Self::Service(entry) => entry.systemd_unit == s,
+            Self::Kernel(e)
+            | Self::Audit(e)
+            | Self::Driver(e)
+            | Self::System(e)
+            | Self::UserManager(e)
+            | Self::Syslog(e)
+            | Self::Journal(e)
+            | Self::Stdout(e) => e,
         }
     }
 
+    /// The unit the entry is about: for the managers that's the one they
+    /// report on, for everyone else the one they run in, a user unit first.
     pub fn unit(&self) -> &str {
+        let field = |names: &[&str]| names.iter().find_map(|name| self.field(name));
         match self {
-            Self::System(e) => e.unit.as_str(),
-            Self::Service(e) => e.systemd_unit.as_str(),
+            Self::System(_) => field(&["UNIT", "_SYSTEMD_UNIT"]),
+            Self::UserManager(_) => field(&["USER_UNIT", "_SYSTEMD_USER_UNIT"]),
+            _ => field(&["_SYSTEMD_USER_UNIT", "_SYSTEMD_UNIT"]),
         }
+        .unwrap_or_default()
     }
 
     pub fn message(&self) -> &str {
-        match self {
-            Self::System(e) => e.message.as_str(),
-            Self::Service(e) => e.message.as_str(),
-        }
+        self.common().message.as_str()
+    }
+
+    /// The value of a field, e.g. `_PID` or `CODE_FILE`.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.common().fields.get(name).map(String::as_str)
+    }
+
+    pub fn fields(&self) -> &JournalRecord {
+        &self.common().fields
     }
 
     pub fn priority(&self) -> Priority {
-        match self {
-            Self::System(e) => e.priority,
-            Self::Service(e) => e.priority,
-        }
+        self.common().priority
     }
 
     pub fn kind(&self) -> &str {
         match self {
-            Self::Service(_) => "service",
-            Self::System(_) => "system",
+            Self::Kernel(_) => "kernel",
+            Self::Audit(_) => "audit",
+            Self::Driver(_) => "journald",
+            Self::System(_) => "system manager",
+            Self::UserManager(_) => "user manager",
+            Self::Syslog(_) => "syslog",
+            Self::Journal(_) => "journal",
+            Self::Stdout(_) => "stdout",
         }
     }
 
+    pub fn cursor(&self) -> &str {
+        self.common().cursor.as_str()
+    }
+
     pub fn timestamp(&self) -> DateTime<Local> {
+        self.common().timestamp.into()
+    }
+
+    /// Microseconds since the epoch, as in `__REALTIME_TIMESTAMP`.
+    pub fn realtime(&self) -> u64 {
+        self.common()
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_micros() as u64)
+    }
+
+    pub fn monotonic(&self) -> u64 {
+        self.common().monotonic
+    }
+
+    /// An entry read back from what `journalctl --output=export` or `json`
+    /// wrote, with the address fields starting with `__` taken out of the
+    /// others; `index` stands in for a missing cursor.
+    pub fn imported(mut fields: JournalRecord, index: usize) -> Entry {
+        let mut number = |name: &str| {
+            fields
+                .remove(name)
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or_default()
+        };
+        let realtime = number("__REALTIME_TIMESTAMP");
+        let monotonic = number("__MONOTONIC_TIMESTAMP");
+        let cursor = fields
+            .remove("__CURSOR")
+            .unwrap_or_else(|| format!("imported-{index}"));
+        fields.retain(|name, _| !name.starts_with("__"));
+        parse_journal_kv(
+            UNIX_EPOCH + Duration::from_micros(realtime),
+            cursor,
+            monotonic,
+            fields,
+        )
+    }
+}
+
+/// Where the entries come from.
+#[derive(Clone)]
+pub enum JournalSource {
+    /// The journal of this machine.
+    Local(OpenOptions),
+    /// A journal directory of another machine, like its `/var/log/journal`.
+    Directory(PathBuf),
+    /// `.journal` files.
+    Files(Vec<PathBuf>),
+    /// A file `journalctl` exported, in the export format or as JSON; read
+    /// into memory, as there is no journal to hand the filter to.
+    Imported(PathBuf),
+}
+
+impl JournalSource {
+    fn open(&self) -> Result<Journal, Error> {
+        Ok(match self {
+            Self::Local(options) => options.open()?,
+            Self::Directory(path) => {
+                OpenDirectoryOptions::default().open_directory(c_path(path)?)?
+            }
+            Self::Files(paths) => {
+                let paths = paths
+                    .iter()
+                    .map(|path| c_path(path))
+                    .collect::<Result<Vec<_>, _>>()?;
+                OpenFilesOptions::default().open_files(paths)?
+            }
+            Self::Imported(_) => return Err(Error::Custom("an imported file isn't a journal")),
+        })
+    }
+
+    /// Whether this is the running system, which makes the current boot
+    /// mean something.
+    pub fn is_local(&self) -> bool {
+        matches!(self, Self::Local(_))
+    }
+
+    /// What the source is, for window titles.
+    pub fn name(&self) -> String {
         match self {
-            Self::Service(e) => e.timestamp,
-            Self::System(e) => e.timestamp,
+            Self::Local(_) => "Journal".to_owned(),
+            Self::Directory(path) | Self::Imported(path) => {
+                format!("Journal: {}", path.display())
+            }
+            Self::Files(paths) => match paths.as_slice() {
+                [path] => format!("Journal: {}", path.display()),
+                paths => format!("Journal: {} files", paths.len()),
+            },
         }
-        .into()
     }
 }
 
+fn c_path(path: &Path) -> Result<CString, Error> {
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::Custom("a path has a NUL byte"))
+}
+
+/// Which entries to read, handed to the journal as matches so its indexes
+/// do the filtering, see sd_journal_add_match(3).
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct JournalFilter {
+    /// Entries logged by or about any of these units.
+    pub units: Vec<String>,
+    /// The least important priority to show.
+    pub priority: Option<Priority>,
+    /// The boot ID, in hex.
+    pub boot: Option<String>,
+    /// Exact `FIELD=value` matches; values of the same field are alternatives.
+    pub fields: Vec<(String, String)>,
+    /// The time range, in microseconds since the epoch. Unlike the others,
+    /// these aren't matches but where reading starts and stops.
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+impl JournalFilter {
+    /// Whether an entry logged at `usec` is before, in or after the time
+    /// range.
+    fn range(&self, usec: u64) -> Ordering {
+        if matches!(self.since, Some(since) if usec < since) {
+            Ordering::Less
+        } else if matches!(self.until, Some(until) if usec > until) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    /// Whether `entry` passes, for entries that aren't in a journal to match
+    /// on; the same as [`Self::apply`] and the time range together.
+    fn matches(&self, entry: &Entry) -> bool {
+        let units = self.units.is_empty()
+            || UNIT_FIELDS
+                .iter()
+                .filter_map(|field| entry.field(field))
+                .any(|unit| self.units.iter().any(|wanted| wanted == unit));
+        let priority = match self.priority {
+            Some(priority) => {
+                entry.field("PRIORITY").is_some() && entry.priority().level() <= priority.level()
+            }
+            None => true,
+        };
+        let boot = match &self.boot {
+            Some(boot) => entry.field("_BOOT_ID") == Some(boot.as_str()),
+            None => true,
+        };
+        let fields = self.fields.iter().all(|(field, _)| {
+            self.fields
+                .iter()
+                .any(|(name, value)| name == field && entry.field(name) == Some(value.as_str()))
+        });
+        units && priority && boot && fields && self.range(entry.realtime()) == Ordering::Equal
+    }
+
+    /// The matches to add, in order, see sd_journal_add_match(3): values of
+    /// the same field are alternatives, different fields all have to match.
+    fn terms(&self) -> Vec<MatchTerm> {
+        let mut terms = Vec::new();
+        if !self.units.is_empty() {
+            // What the unit logged, or what its manager logged about it,
+            // like `journalctl --unit` and `--user-unit`.
+            for (index, unit) in self.units.iter().enumerate() {
+                for (term, field) in UNIT_FIELDS.into_iter().enumerate() {
+                    if index > 0 || term > 0 {
+                        terms.push(MatchTerm::Or);
+                    }
+                    terms.push(MatchTerm::Add(field.to_owned(), unit.clone()));
+                }
+            }
+            terms.push(MatchTerm::And);
+        }
+        if let Some(priority) = self.priority {
+            for level in 0..=priority.level() {
+                terms.push(MatchTerm::Add("PRIORITY".to_owned(), level.to_string()));
+            }
+        }
+        if let Some(boot) = &self.boot {
+            terms.push(MatchTerm::Add("_BOOT_ID".to_owned(), boot.clone()));
+        }
+        for (field, value) in &self.fields {
+            terms.push(MatchTerm::Add(field.clone(), value.clone()));
+        }
+        terms
+    }
+
+    fn apply(&self, reader: &mut Journal) -> Result<(), Error> {
+        for term in self.terms() {
+            match term {
+                MatchTerm::Add(field, value) => reader.match_add(&field, value)?,
+                MatchTerm::Or => reader.match_or()?,
+                MatchTerm::And => reader.match_and()?,
+            };
+        }
+        Ok(())
+    }
+}
+
+/// The fields naming the unit an entry is from or about.
+const UNIT_FIELDS: [&str; 4] = ["_SYSTEMD_UNIT", "UNIT", "_SYSTEMD_USER_UNIT", "USER_UNIT"];
+
+/// One call building up the matches of a [`Journal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MatchTerm {
+    /// `FIELD=value`
+    Add(String, String),
+    /// What comes next is an alternative to what came before.
+    Or,
+    /// What comes next has to match as well as what came before.
+    And,
+}
+
+/// The ID of the running boot in hex, as in `_BOOT_ID`.
+pub fn current_boot() -> Option<String> {
+    let id = std::fs::read_to_string("/proc/sys/kernel/random/boot_id").ok()?;
+    Some(id.trim().replace('-', ""))
+}
+
+/// A boot that has entries in the journal.
+#[derive(Debug, Clone)]
+pub struct Boot {
+    /// In hex, as in `_BOOT_ID`.
+    pub id: String,
+    pub first: DateTime<Local>,
+    pub last: DateTime<Local>,
+}
+
+/// Every boot in the journal, oldest first, like `journalctl --list-boots`.
+///
+/// Each boot ID in the journal is matched on in turn, for its first and last
+/// entry.
+pub fn list_boots(source: &JournalSource) -> Result<Vec<Boot>, Error> {
+    let mut boots: Vec<Boot> = Vec::new();
+    if let JournalSource::Imported(path) = source {
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        for entry in import::import(path)? {
+            let Some(id) = entry.field("_BOOT_ID") else {
+                continue;
+            };
+            match indices.get(id) {
+                Some(&index) => boots[index].last = entry.timestamp(),
+                None => {
+                    indices.insert(id.to_owned(), boots.len());
+                    boots.push(Boot {
+                        id: id.to_owned(),
+                        first: entry.timestamp(),
+                        last: entry.timestamp(),
+                    });
+                }
+            }
+        }
+    } else {
+        let mut reader = source.open()?;
+        for id in query_unique(&mut reader, "_BOOT_ID")? {
+            reader.match_add("_BOOT_ID", id.as_str())?;
+            reader.seek_head()?;
+            let found = reader.next()? > 0;
+            let first = reader.timestamp();
+            reader.seek_tail()?;
+            reader.previous()?;
+            let last = reader.timestamp();
+            reader.match_flush()?;
+            // Only in a file that's gone since, or was rotated out.
+            if !found {
+                continue;
+            }
+            boots.push(Boot {
+                id,
+                first: first?.into(),
+                last: last?.into(),
+            });
+        }
+    }
+    // The clock may have been set back, so the order of the entries isn't
+    // necessarily the order of the boots either.
+    boots.sort_by_key(|boot| boot.first);
+    Ok(boots)
+}
+
+/// The values `field` has in the journal, see sd_journal_query_unique(3),
+/// which the systemd crate doesn't wrap.
+fn query_unique(reader: &mut Journal, field: &str) -> Result<Vec<String>, Error> {
+    use libsystemd_sys::journal::{sd_journal_enumerate_unique, sd_journal_query_unique};
+
+    let journal = reader.as_ptr();
+    let name = CString::new(field).map_err(|_| Error::Custom("a field name has a NUL byte"))?;
+    // SAFETY: the journal stays open while `reader` is borrowed, and the name
+    // is a C string.
+    let result = unsafe { sd_journal_query_unique(journal, name.as_ptr()) };
+    if result < 0 {
+        return Err(std::io::Error::from_raw_os_error(-result).into());
+    }
+    let mut values = Vec::new();
+    loop {
+        let mut data = std::ptr::null();
+        let mut length = 0;
+        // SAFETY: as above, and both pointers are to locals.
+        let result = unsafe { sd_journal_enumerate_unique(journal, &mut data, &mut length) };
+        if result == 0 {
+            break;
+        }
+        if result < 0 {
+            return Err(std::io::Error::from_raw_os_error(-result).into());
+        }
+        // SAFETY: the journal hands out `length` bytes at `data`, valid until
+        // the next call on it.
+        let data = unsafe { std::slice::from_raw_parts(data.cast::<u8>(), length) };
+        // Each is `FIELD=value`.
+        if let Some(value) = data.get(field.len() + 1..) {
+            values.push(String::from_utf8_lossy(value).into_owned());
+        }
+    }
+    Ok(values)
+}
+
+/// Most entries kept in memory at once; scrolling further pages entries in
+/// and drops the ones at the other end.
+pub const WINDOW_CAPACITY: usize = 10_000;
+/// How many entries are read at a time when scrolling past the window.
+const PAGE_SIZE: usize = 500;
+/// How long the worker waits for new entries before looking for requests,
+/// and so how long it takes to notice the window went away.
+const WAIT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Asks the worker for the entries next to the window.
+#[derive(Debug)]
+enum Request {
+    /// The entries before the one at `cursor`.
+    Older { cursor: String, count: usize },
+    /// The entries after the one at `cursor`.
+    Newer { cursor: String, count: usize },
+}
+
+#[derive(Debug)]
+pub enum Update {
+    /// Entries logged after the newest one read so far, oldest first.
+    Appended(Vec<Entry>),
+    /// Entries before the oldest in the window, oldest first.
+    Older { entries: Vec<Entry>, at_head: bool },
+    /// Entries after the newest in the window, oldest first.
+    Newer { entries: Vec<Entry>, at_tail: bool },
+}
+
+/// A sliding window over the journal, starting at its tail.
+///
+/// Only up to [`WINDOW_CAPACITY`] entries are kept. The window moves by
+/// asking the worker for the entries before its first cursor or after its
+/// last one.
 pub struct JournalReader {
-    entries: Vec<Entry>,
-    receiver: Receiver<Entry>,
-    work: JoinHandle<Result<(), Error>>,
+    entries: VecDeque<Entry>,
+    receiver: Receiver<Update>,
+    requests: UnboundedSender<Request>,
+    /// Taken once the worker stops, for what went wrong.
+    work: Option<JoinHandle<Result<(), Error>>>,
+    error: Option<String>,
+    /// The oldest entry of the journal is in the window.
+    at_head: bool,
+    /// The newest entry of the journal is in the window, so appended ones
+    /// belong to it.
+    at_tail: bool,
+    /// A page was asked for and hasn't come yet.
+    loading: bool,
+    /// How many entries were put in front of the window since the last
+    /// `take_prepended`, as they move the indices of the others.
+    prepended: usize,
+    /// How many entries were dropped off the front of the window since the
+    /// last `take_trimmed`, moving the indices of the others back.
+    trimmed: usize,
+    /// Appended entries are held back in `pending` instead of scrolling the
+    /// window.
+    paused: bool,
+    pending: Vec<Entry>,
 }
 
 impl JournalReader {
-    pub fn new(options: OpenOptions) -> Self {
+    /// Starts reading at the tail of the journal, with the last `backlog`
+    /// entries that pass `filter`.
+    pub fn new(source: JournalSource, filter: JournalFilter, backlog: usize) -> Self {
         let (sender, receiver) = tokio::sync::mpsc::channel(64);
-        let work = tokio::task::spawn_blocking(|| worker(sender, options));
+        let (requests, requests_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let work = tokio::task::spawn_blocking(move || match source {
+            JournalSource::Imported(path) => {
+                memory_worker(sender, requests_receiver, path, filter, backlog)
+            }
+            source => worker(sender, requests_receiver, source, filter, backlog),
+        });
 
         Self {
-            entries: Vec::default(),
+            entries: VecDeque::default(),
             receiver,
-            work,
+            requests,
+            work: Some(work),
+            error: None,
+            at_head: false,
+            at_tail: true,
+            loading: true,
+            prepended: 0,
+            trimmed: 0,
+            paused: false,
+            pending: Vec::new(),
         }
     }
-    pub fn receive(&mut self) {
+
+    /// Takes in what the worker read, returns whether the window changed.
+    pub fn receive(&mut self) -> bool {
+        let mut changed = false;
         loop {
             let receive = self.receiver.try_recv();
             match receive {
-                Ok(entry) => {
-                    self.entries.push(entry);
+                // Past the end of the window, they get paged in later.
+                Ok(Update::Appended(_)) if !self.at_tail => (),
+                Ok(Update::Appended(entries)) if self.paused => {
+                    self.pending.extend(entries);
+                    // Too many to hold on to; paging in from the window
+                    // catches up without leaving a gap.
+                    if self.pending.len() > WINDOW_CAPACITY {
+                        self.pending.clear();
+                        self.at_tail = false;
+                    }
+                    changed = true;
+                }
+                Ok(Update::Appended(entries)) => {
+                    self.entries.extend(entries);
+                    self.trim_front();
+                    changed = true;
+                }
+                Ok(Update::Older { entries, at_head }) => {
+                    self.loading = false;
+                    self.at_head = at_head;
+                    self.prepended += entries.len();
+                    for entry in entries.into_iter().rev() {
+                        self.entries.push_front(entry);
+                    }
+                    if self.entries.len() > WINDOW_CAPACITY {
+                        self.entries.truncate(WINDOW_CAPACITY);
+                        self.at_tail = false;
+                    }
+                    changed = true;
+                }
+                Ok(Update::Newer { entries, at_tail }) => {
+                    self.loading = false;
+                    self.at_tail = at_tail;
+                    self.entries.extend(entries);
+                    self.trim_front();
+                    changed = true;
                 }
 
                 Err(err) => match err {
-                    TryRecvError::Empty => return,
+                    TryRecvError::Empty => return changed,
+                    // The worker stopped, on an error or at the end of an
+                    // imported file.
                     TryRecvError::Disconnected => {
-                        panic!("This should have been dropped")
+                        if let Some(work) = self.work.take() {
+                            self.loading = false;
+                            self.error = match Promise::spawn_async(work).block_and_take() {
+                                Ok(Ok(())) => None,
+                                Ok(Err(err)) => Some(err.to_string()),
+                                Err(err) => Some(err.to_string()),
+                            };
+                            changed = true;
+                        }
+                        return changed;
                     }
                 },
             }
         }
     }
 
-    pub fn continue_filter<P, C>(&self, predicate: &mut P, container: &mut C, last: usize)
-    where
-        P: FnMut(&Entry) -> bool,
-        C: std::iter::Extend<usize>,
-    {
-        container.extend(self.entries.iter().enumerate().skip(last + 1).filter_map(
-            |(index, entry)| {
-                if predicate(entry) {
-                    Some(index)
-                } else {
-                    None
-                }
-            },
-        ));
+    fn trim_front(&mut self) {
+        while self.entries.len() > WINDOW_CAPACITY {
+            self.entries.pop_front();
+            self.trimmed += 1;
+            self.at_head = false;
+        }
     }
 
-    pub fn filter<P>(&self, predicate: &mut P) -> Vec<usize>
-    where
-        P: FnMut(&Entry) -> bool,
-    {
-        self.entries
-            .iter()
-            .enumerate()
-            .filter_map(
-                |(index, entry)| {
-                    if predicate(entry) {
-                        Some(index)
-                    } else {
-                        None
-                    }
-                },
-            )
-            .collect()
+    /// Pages in the entries before the window, unless it starts at the head of
+    /// the journal.
+    pub fn load_older(&mut self) {
+        if self.loading || self.at_head {
+            return;
+        }
+        if let Some(first) = self.entries.front() {
+            self.loading = self
+                .requests
+                .send(Request::Older {
+                    cursor: first.cursor().to_owned(),
+                    count: PAGE_SIZE,
+                })
+                .is_ok();
+        }
+    }
+
+    /// Pages in the entries after the window, unless it ends at the tail of
+    /// the journal.
+    pub fn load_newer(&mut self) {
+        if self.loading || self.at_tail {
+            return;
+        }
+        if let Some(last) = self.entries.back() {
+            self.loading = self
+                .requests
+                .send(Request::Newer {
+                    cursor: last.cursor().to_owned(),
+                    count: PAGE_SIZE,
+                })
+                .is_ok();
+        }
+    }
+
+    /// Why the worker stopped, if it failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    pub fn at_head(&self) -> bool {
+        self.at_head
+    }
+
+    pub fn at_tail(&self) -> bool {
+        self.at_tail
+    }
+
+    /// Holds back appended entries, or puts the held back ones in the window.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        if !paused {
+            self.entries.extend(self.pending.drain(..));
+            self.trim_front();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// How many entries were held back while paused.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// How many entries were put in front of the window since the last call.
+    pub fn take_prepended(&mut self) -> usize {
+        std::mem::take(&mut self.prepended)
+    }
+
+    /// How many entries were dropped off the front of the window since the
+    /// last call.
+    pub fn take_trimmed(&mut self) -> usize {
+        std::mem::take(&mut self.trimmed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 }
 
-impl<I: SliceIndex<[Entry]>> Index<I> for JournalReader {
-    type Output = I::Output;
+impl Index<usize> for JournalReader {
+    type Output = Entry;
 
     #[inline]
-    fn index(&self, index: I) -> &Self::Output {
+    fn index(&self, index: usize) -> &Self::Output {
         self.entries.index(index)
     }
 }
 
-fn parse_journal_kv(timestamp: SystemTime, fields_iter: Iter<'_, String, String>) -> Entry {
-    let mut common = EntryCommon::with_timestamp(timestamp);
-    fields_iter.for_each(|(k, v)| match k.as_ref() {
-        "_SYSTEMD_SLICE" => common.slice = v.clone(),
-        "_SYSTEMD_UNIT" => common.systemd_unit = v.clone(),
-        "SYSLOG_IDENTIFIER" => common.syslog_id = v.clone(),
-        "MESSAGE" => common.message = v.clone(),
-        "UNIT" => common.unit = v.clone(),
-        "PRIORITY" => common.priority = v.clone().into(),
-        _ => {
-            if cfg!(feature = "panic-for-unimplementd") {
-                panic!("implement parsing for {k}")
+fn parse_journal_kv(
+    timestamp: SystemTime,
+    cursor: String,
+    monotonic: u64,
+    fields: JournalRecord,
+) -> Entry {
+    let common = EntryCommon {
+        timestamp,
+        cursor,
+        monotonic,
+        message: fields.get("MESSAGE").cloned().unwrap_or_default(),
+        priority: fields
+            .get("PRIORITY")
+            .and_then(|priority| Priority::try_from(priority.as_str()).ok())
+            .unwrap_or_default(),
+        fields,
+    };
+
+    let field = |name: &str| common.fields.get(name).map(String::as_str);
+    // The managers log through the journal like everyone else, so tell them
+    // apart by who they are; a user manager runs as `init.scope` of its
+    // `user@.service`.
+    let manager = field("SYSLOG_IDENTIFIER") == Some("systemd");
+    match field("_TRANSPORT") {
+        Some("kernel") => Entry::Kernel(common),
+        Some("audit") => Entry::Audit(common),
+        Some("driver") => Entry::Driver(common),
+        _ if manager && field("_PID") == Some("1") => Entry::System(common),
+        _ if manager && field("_SYSTEMD_USER_UNIT") == Some("init.scope") => {
+            Entry::UserManager(common)
+        }
+        Some("syslog") => Entry::Syslog(common),
+        Some("stdout") => Entry::Stdout(common),
+        _ => Entry::Journal(common),
+    }
+}
+
+/// Makes an [`Entry`] of the record the reader is at.
+fn read_entry(reader: &Journal, record: JournalRecord) -> Result<Entry, Error> {
+    Ok(parse_journal_kv(
+        reader.timestamp()?,
+        reader.cursor()?,
+        reader.monotonic_timestamp()?.0,
+        record,
+    ))
+}
+
+/// When the entry the reader is at was logged, in microseconds since the
+/// epoch, as the systemd crate only has it as a `SystemTime`.
+fn realtime_usec(reader: &Journal) -> Result<u64, Error> {
+    let since_epoch = reader
+        .timestamp()?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(since_epoch.as_micros() as u64)
+}
+
+/// Reads up to `count` entries of the filter's time range from where the
+/// reader is, backwards if `older`, and returns them oldest first either way.
+/// Also tells whether the end of the journal or of the range was reached.
+///
+/// The entry at `skip` is left out, as seeking to a cursor lands on its
+/// entry, which is in the window already.
+fn read_entries(
+    reader: &mut Journal,
+    filter: &JournalFilter,
+    skip: Option<&str>,
+    count: usize,
+    older: bool,
+) -> Result<(Vec<Entry>, bool), Error> {
+    let mut entries = Vec::with_capacity(count);
+    let mut ended = false;
+    while entries.len() < count {
+        let record = if older {
+            reader.previous_entry()?
+        } else {
+            reader.next_entry()?
+        };
+        let Some(record) = record else {
+            ended = true;
+            break;
+        };
+        if let Some(cursor) = skip {
+            if reader.test_cursor(cursor)? {
+                continue;
             }
         }
-    });
+        match (filter.range(realtime_usec(reader)?), older) {
+            (Ordering::Equal, _) => entries.push(read_entry(reader, record)?),
+            // Not there yet, e.g. after seeking to the end of the range.
+            (Ordering::Less, false) | (Ordering::Greater, true) => (),
+            _ => {
+                ended = true;
+                break;
+            }
+        }
+    }
+    if older {
+        entries.reverse();
+    }
+    Ok((entries, ended))
+}
 
-    if common.syslog_id == "systemd" {
-        Entry::System(common)
-    } else {
-        Entry::Service(common)
+fn worker(
+    sender: Sender<Update>,
+    mut requests: UnboundedReceiver<Request>,
+    source: JournalSource,
+    filter: JournalFilter,
+    backlog: usize,
+) -> Result<(), Error> {
+    let mut reader = source.open()?;
+    filter.apply(&mut reader)?;
+    match filter.until {
+        Some(until) => reader.seek_realtime_usec(until)?,
+        None => reader.seek_tail()?,
     }
+    let (backlog_entries, at_head) = read_entries(&mut reader, &filter, None, backlog, true)?;
+    // The newest entry read, to carry on from after paging moved the reader.
+    let mut tail = backlog_entries
+        .last()
+        .map(|entry| entry.cursor().to_owned());
+    let mut moved = true;
+    sender.blocking_send(Update::Older {
+        entries: backlog_entries,
+        at_head,
+    })?;
+
+    loop {
+        let request = match requests.try_recv() {
+            Ok(request) => Some(request),
+            Err(TryRecvError::Empty) => None,
+            // The window was closed or reloaded.
+            Err(TryRecvError::Disconnected) => break,
+        };
+        if let Some(request) = request {
+            let update = match request {
+                Request::Older { cursor, count } => {
+                    reader.seek_cursor(&cursor)?;
+                    let (entries, at_head) =
+                        read_entries(&mut reader, &filter, Some(&cursor), count, true)?;
+                    Update::Older { entries, at_head }
+                }
+                Request::Newer { cursor, count } => {
+                    reader.seek_cursor(&cursor)?;
+                    let (entries, at_tail) =
+                        read_entries(&mut reader, &filter, Some(&cursor), count, false)?;
+                    // What the page read up to the end mustn't come again as
+                    // appended.
+                    if at_tail {
+                        if let Some(last) = entries.last() {
+                            tail = Some(last.cursor().to_owned());
+                        }
+                    }
+                    Update::Newer { entries, at_tail }
+                }
+            };
+            moved = true;
+            if sender.blocking_send(update).is_err() {
+                break;
+            }
+            continue;
+        }
+        if moved {
+            match &tail {
+                Some(cursor) => {
+                    reader.seek_cursor(cursor)?;
+                    reader.next()?;
+                }
+                None => reader.seek_head()?,
+            }
+            moved = false;
+        }
+
+        let mut batch = Vec::new();
+        while batch.len() < PAGE_SIZE {
+            let Some(record) = reader.next_entry()? else {
+                break;
+            };
+            let entry = read_entry(&reader, record)?;
+            tail = Some(entry.cursor().to_owned());
+            if filter.range(realtime_usec(&reader)?) == Ordering::Equal {
+                batch.push(entry);
+            }
+        }
+        // Otherwise there is more to read right away.
+        let caught_up = batch.len() < PAGE_SIZE;
+        if !batch.is_empty() && sender.blocking_send(Update::Appended(batch)).is_err() {
+            break;
+        }
+        if caught_up {
+            // Sleeps in sd_journal_wait(3) until something is logged, waking
+            // up now and then for requests.
+            reader.wait(Some(WAIT_TIMEOUT))?;
+        }
+    }
+    Ok(())
 }
 
-fn worker(sender: Sender<Entry>, options: OpenOptions) -> Result<(), Error> {
-    let mut reader = options.open()?;
-    while !sender.is_closed() {
-        while let Some(entry) = reader.next_entry()? {
-            let timestamp = reader.timestamp()?;
-            let entry = parse_journal_kv(timestamp, entry.iter());
-            let sender = sender.clone();
+/// The worker for an imported file: reads it all, then pages through the
+/// entries that pass `filter` the same way.
+fn memory_worker(
+    sender: Sender<Update>,
+    mut requests: UnboundedReceiver<Request>,
+    path: PathBuf,
+    filter: JournalFilter,
+    backlog: usize,
+) -> Result<(), Error> {
+    let entries: Vec<Entry> = import::import(&path)?
+        .into_iter()
+        .filter(|entry| filter.matches(entry))
+        .collect();
+    let page = |range: Range<usize>| entries[range].to_vec();
+    let start = entries.len().saturating_sub(backlog);
+    sender.blocking_send(Update::Older {
+        entries: page(start..entries.len()),
+        at_head: start == 0,
+    })?;
 
-            Promise::spawn_async(async move { sender.send(entry).await }).block_and_take()?;
+    while let Some(request) = requests.blocking_recv() {
+        let position = |cursor: &str| entries.iter().position(|entry| entry.cursor() == cursor);
+        let update = match request {
+            Request::Older { cursor, count } => {
+                let end = position(&cursor).unwrap_or(0);
+                let start = end.saturating_sub(count);
+                Update::Older {
+                    entries: page(start..end),
+                    at_head: start == 0,
+                }
+            }
+            Request::Newer { cursor, count } => {
+                let start = position(&cursor).map_or(entries.len(), |index| index + 1);
+                let end = (start + count).min(entries.len());
+                Update::Newer {
+                    entries: page(start..end),
+                    at_tail: end == entries.len(),
+                }
+            }
+        };
+        if sender.blocking_send(update).is_err() {
+            break;
         }
-        reader.wait(Some(Duration::from_micros(1)))?;
     }
     Ok(())
 }
+
+/// Hands every entry that passes `filter` to `each`, oldest first, one at a
+/// time so the whole journal never has to fit in memory. Returns how many
+/// there were.
+pub fn read_all(
+    source: &JournalSource,
+    filter: &JournalFilter,
+    mut each: impl FnMut(&Entry) -> Result<(), Error>,
+) -> Result<usize, Error> {
+    if let JournalSource::Imported(path) = source {
+        let mut count = 0;
+        for entry in import::import(path)? {
+            if filter.matches(&entry) {
+                each(&entry)?;
+                count += 1;
+            }
+        }
+        return Ok(count);
+    }
+    let mut reader = source.open()?;
+    filter.apply(&mut reader)?;
+    match filter.since {
+        Some(since) => reader.seek_realtime_usec(since)?,
+        None => reader.seek_head()?,
+    }
+    let mut count = 0;
+    while let Some(record) = reader.next_entry()? {
+        match filter.range(realtime_usec(&reader)?) {
+            Ordering::Less => continue,
+            Ordering::Greater => break,
+            Ordering::Equal => (),
+        }
+        let entry = read_entry(&reader, record)?;
+        each(&entry)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Reads the last `count` entries logged by, or about, one invocation of a
+/// unit, oldest first.
+pub fn invocation_entries(
+    options: OpenOptions,
+    invocation: &str,
+    count: usize,
+) -> Result<Vec<Entry>, Error> {
+    let mut reader = options.open()?;
+    // The unit's own output, then what the system or user manager said
+    // about it.
+    reader.match_add("_SYSTEMD_INVOCATION_ID", invocation)?;
+    reader.match_or()?;
+    reader.match_add("INVOCATION_ID", invocation)?;
+    reader.match_or()?;
+    reader.match_add("USER_INVOCATION_ID", invocation)?;
+    reader.seek_tail()?;
+
+    let mut entries = Vec::with_capacity(count);
+    while entries.len() < count {
+        match reader.previous_entry()? {
+            Some(record) => entries.push(read_entry(&reader, record)?),
+            None => break,
+        }
+    }
+    entries.reverse();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(fields: &[(&str, &str)]) -> Entry {
+        let record = fields
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string()))
+            .collect();
+        Entry::imported(record, 0)
+    }
+
+    /// Whether the journal would return `entry` for `terms`: the `Or`s split
+    /// the conjunctions of each `And`ed group, and in a conjunction values of
+    /// the same field are alternatives.
+    fn journal_matches(terms: &[MatchTerm], entry: &Entry) -> bool {
+        let mut groups: Vec<Vec<Vec<(&str, &str)>>> = vec![vec![Vec::new()]];
+        for term in terms {
+            match term {
+                MatchTerm::Add(field, value) => groups
+                    .last_mut()
+                    .and_then(|group| group.last_mut())
+                    .unwrap()
+                    .push((field, value)),
+                MatchTerm::Or => groups.last_mut().unwrap().push(Vec::new()),
+                MatchTerm::And => groups.push(vec![Vec::new()]),
+            }
+        }
+        groups.iter().all(|alternatives| {
+            let mut alternatives = alternatives.iter().filter(|terms| !terms.is_empty());
+            let mut any = false;
+            let matched = alternatives.any(|terms| {
+                any = true;
+                terms.iter().all(|(field, _)| {
+                    terms
+                        .iter()
+                        .any(|(name, value)| name == field && entry.field(name) == Some(value))
+                })
+            });
+            matched || !any
+        })
+    }
+
+    fn check(filter: &JournalFilter, entry: &Entry, expected: bool) {
+        assert_eq!(filter.matches(entry), expected, "{filter:?} on {entry:?}");
+        assert_eq!(
+            journal_matches(&filter.terms(), entry),
+            expected,
+            "terms of {filter:?} on {entry:?}"
+        );
+    }
+
+    #[test]
+    fn values_of_a_field_are_alternatives() {
+        let filter = JournalFilter {
+            fields: vec![
+                ("_COMM".to_owned(), "sshd".to_owned()),
+                ("_COMM".to_owned(), "sudo".to_owned()),
+            ],
+            ..Default::default()
+        };
+        check(&filter, &entry(&[("_COMM", "sshd")]), true);
+        check(&filter, &entry(&[("_COMM", "sudo")]), true);
+        check(&filter, &entry(&[("_COMM", "bash")]), false);
+        check(&filter, &entry(&[]), false);
+    }
+
+    #[test]
+    fn different_fields_all_have_to_match() {
+        let filter = JournalFilter {
+            boot: Some("b1".to_owned()),
+            fields: vec![("_COMM".to_owned(), "sshd".to_owned())],
+            ..Default::default()
+        };
+        check(
+            &filter,
+            &entry(&[("_COMM", "sshd"), ("_BOOT_ID", "b1")]),
+            true,
+        );
+        check(
+            &filter,
+            &entry(&[("_COMM", "sshd"), ("_BOOT_ID", "b2")]),
+            false,
+        );
+        check(
+            &filter,
+            &entry(&[("_COMM", "sudo"), ("_BOOT_ID", "b1")]),
+            false,
+        );
+    }
+
+    #[test]
+    fn priority_is_a_ceiling() {
+        let filter = JournalFilter {
+            priority: Some(Priority::Warning),
+            ..Default::default()
+        };
+        check(&filter, &entry(&[("PRIORITY", "0")]), true);
+        check(&filter, &entry(&[("PRIORITY", "4")]), true);
+        check(&filter, &entry(&[("PRIORITY", "5")]), false);
+        check(&filter, &entry(&[]), false);
+    }
+
+    #[test]
+    fn units_match_any_unit_field() {
+        let filter = JournalFilter {
+            units: vec!["a.service".to_owned(), "b.service".to_owned()],
+            priority: Some(Priority::Error),
+            ..Default::default()
+        };
+        check(
+            &filter,
+            &entry(&[("_SYSTEMD_UNIT", "a.service"), ("PRIORITY", "3")]),
+            true,
+        );
+        check(
+            &filter,
+            &entry(&[("UNIT", "b.service"), ("PRIORITY", "2")]),
+            true,
+        );
+        check(
+            &filter,
+            &entry(&[("USER_UNIT", "a.service"), ("PRIORITY", "3")]),
+            true,
+        );
+        check(
+            &filter,
+            &entry(&[("_SYSTEMD_UNIT", "c.service"), ("PRIORITY", "3")]),
+            false,
+        );
+        check(
+            &filter,
+            &entry(&[("_SYSTEMD_UNIT", "a.service"), ("PRIORITY", "6")]),
+            false,
+        );
+    }
+
+    #[test]
+    fn time_range_is_inclusive() {
+        let filter = JournalFilter {
+            since: Some(100),
+            until: Some(200),
+            ..Default::default()
+        };
+        let at = |usec: &str| entry(&[("__REALTIME_TIMESTAMP", usec)]);
+        assert!(filter.matches(&at("100")));
+        assert!(filter.matches(&at("200")));
+        assert!(!filter.matches(&at("99")));
+        assert!(!filter.matches(&at("201")));
+    }
+
+    #[test]
+    fn apply_adds_the_unit_alternatives_first() {
+        let filter = JournalFilter {
+            units: vec!["a.service".to_owned()],
+            boot: Some("b1".to_owned()),
+            ..Default::default()
+        };
+        let add = |field: &str, value: &str| MatchTerm::Add(field.to_owned(), value.to_owned());
+        assert_eq!(
+            filter.terms(),
+            vec![
+                add("_SYSTEMD_UNIT", "a.service"),
+                MatchTerm::Or,
+                add("UNIT", "a.service"),
+                MatchTerm::Or,
+                add("_SYSTEMD_USER_UNIT", "a.service"),
+                MatchTerm::Or,
+                add("USER_UNIT", "a.service"),
+                MatchTerm::And,
+                add("_BOOT_ID", "b1"),
+            ]
+        );
+    }
+}
@@ -5,8 +5,16 @@ use widgets::system_overview::Overview;
 
 mod app;
 pub mod error;
+mod exit_status;
+mod export;
+mod format;
+mod import;
 pub mod journal;
 pub mod message;
+mod procfs;
+mod search;
+mod security;
+mod signal;
 mod systemd;
 mod widgets;
 
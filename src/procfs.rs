@@ -0,0 +1,368 @@
+//! Readers for the cgroup and proc pseudo-filesystems.
+//!
+//! They all work relative to a root directory, which is `/` on a live system,
+//! so they can be pointed at a copy of the relevant files instead.
+
+use chrono::{DateTime, Local, TimeZone};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The `st` column of a listening socket in `/proc/net/tcp`.
+const TCP_LISTEN: &str = "0A";
+
+#[derive(Debug, Clone)]
+pub struct SysRoot {
+    root: PathBuf,
+}
+
+impl Default for SysRoot {
+    fn default() -> Self {
+        Self::new("/")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// The arguments joined by spaces, or the `[comm]` for kernel threads.
+    pub command_line: String,
+    pub uid: Option<u32>,
+    /// Resident set size in bytes.
+    pub rss: Option<u64>,
+    pub started: Option<DateTime<Local>>,
+}
+
+impl SysRoot {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        self.root.join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    /// Lists the PIDs in `control_group` (as in the `ControlGroup` property,
+    /// e.g. `/system.slice/sshd.service`) and all of its child groups.
+    pub fn cgroup_procs(&self, control_group: &str) -> io::Result<Vec<u32>> {
+        let mut pids = Vec::new();
+        let mut pending = vec![self
+            .path("/sys/fs/cgroup")
+            .join(control_group.trim_start_matches('/'))];
+        while let Some(dir) = pending.pop() {
+            let procs = fs::read_to_string(dir.join("cgroup.procs"))?;
+            pids.extend(
+                procs
+                    .lines()
+                    .filter_map(|line| line.trim().parse::<u32>().ok()),
+            );
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    pending.push(entry.path());
+                }
+            }
+        }
+        pids.sort_unstable();
+        Ok(pids)
+    }
+
+    pub fn process(&self, pid: u32) -> io::Result<ProcessInfo> {
+        let dir = self.path("/proc").join(pid.to_string());
+        let stat = fs::read_to_string(dir.join("stat"))?;
+        // The command name is in parentheses and may contain spaces itself.
+        let (comm, rest) = match (stat.find('('), stat.rfind(')')) {
+            (Some(open), Some(close)) if open < close => {
+                (&stat[open + 1..close], &stat[close + 1..])
+            }
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "malformed stat")),
+        };
+
+        let cmdline = fs::read(dir.join("cmdline"))?;
+        let command_line = if cmdline.is_empty() {
+            format!("[{comm}]")
+        } else {
+            String::from_utf8_lossy(&cmdline)
+                .trim_end_matches('\0')
+                .replace('\0', " ")
+        };
+
+        let status = fs::read_to_string(dir.join("status"))?;
+        let status_field = |name: &str| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .and_then(|value| value.split_whitespace().next())
+        };
+        let uid = status_field("Uid:").and_then(|uid| uid.parse().ok());
+        let rss = status_field("VmRSS:")
+            .and_then(|kib| kib.parse::<u64>().ok())
+            .map(|kib| kib * 1024);
+
+        // starttime is the 22nd field, counting from the pid.
+        let started = rest
+            .split_whitespace()
+            .nth(22 - 3)
+            .and_then(|ticks| ticks.parse::<u64>().ok())
+            .zip(self.boot_time().ok())
+            .and_then(|(ticks, boot)| {
+                let secs = boot + ticks / clock_ticks();
+                Local.timestamp_opt(secs as i64, 0).single()
+            });
+
+        Ok(ProcessInfo {
+            pid,
+            command_line,
+            uid,
+            rss,
+            started,
+        })
+    }
+
+    /// Seconds since the epoch at which the system booted.
+    fn boot_time(&self) -> io::Result<u64> {
+        fs::read_to_string(self.path("/proc/stat"))?
+            .lines()
+            .find_map(|line| line.strip_prefix("btime "))
+            .and_then(|btime| btime.trim().parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no btime in stat"))
+    }
+
+    /// Finds the inodes of the listening TCP and the bound UDP sockets on local
+    /// `port`, as `(table, inode)` where table is one of `tcp`, `tcp6`, `udp`,
+    /// `udp6`.
+    pub fn sockets_on_port(&self, port: u16) -> io::Result<Vec<(&'static str, u64)>> {
+        let mut sockets = Vec::new();
+        for table in ["tcp", "tcp6", "udp", "udp6"] {
+            let content = match fs::read_to_string(self.path("/proc/net").join(table)) {
+                Ok(content) => content,
+                // Not every kernel has IPv6 enabled.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            for line in content.lines().skip(1) {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let (Some(local), Some(state), Some(inode)) =
+                    (fields.get(1), fields.get(3), fields.get(9))
+                else {
+                    continue;
+                };
+                // Connections made to or from the port aren't its owner; UDP
+                // has no listening state.
+                if table.starts_with("tcp") && *state != TCP_LISTEN {
+                    continue;
+                }
+                let local_port = local
+                    .rsplit_once(':')
+                    .and_then(|(_, port)| u16::from_str_radix(port, 16).ok());
+                match (local_port, inode.parse::<u64>()) {
+                    // Inode 0 means the socket is already being torn down.
+                    (Some(local_port), Ok(inode)) if local_port == port && inode != 0 => {
+                        sockets.push((table, inode));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(sockets)
+    }
+
+    /// Finds the processes having a file descriptor whose link target makes
+    /// `matches` return true, e.g. `socket:[1234]` or `/var/log/foo.log`.
+    pub fn pids_with_fd<F>(&self, mut matches: F) -> io::Result<Vec<u32>>
+    where
+        F: FnMut(&Path) -> bool,
+    {
+        let mut pids = Vec::new();
+        for entry in fs::read_dir(self.path("/proc"))? {
+            let entry = entry?;
+            let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+                continue;
+            };
+            // Processes come and go, and most aren't ours to look into.
+            let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+                continue;
+            };
+            if fds
+                .filter_map(Result::ok)
+                .filter_map(|fd| fs::read_link(fd.path()).ok())
+                .any(|target| matches(&target))
+            {
+                pids.push(pid);
+            }
+        }
+        pids.sort_unstable();
+        Ok(pids)
+    }
+
+    /// Guesses the unit of `pid` from the innermost unit in its control
+    /// group path, for when the manager can't be asked.
+    pub fn unit_of_pid(&self, pid: u32) -> io::Result<Option<String>> {
+        let cgroup = fs::read_to_string(self.path("/proc").join(pid.to_string()).join("cgroup"))?;
+        // Prefer the unified hierarchy, then the named systemd one of v1.
+        let path = cgroup
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .or_else(|| {
+                cgroup
+                    .lines()
+                    .find_map(|line| line.split_once(":name=systemd:").map(|(_, p)| p))
+            });
+        Ok(path.and_then(|path| {
+            path.rsplit('/')
+                .find(|component| {
+                    [".service", ".scope", ".socket", ".mount", ".swap"]
+                        .iter()
+                        .any(|suffix| component.ends_with(suffix))
+                })
+                .map(str::to_owned)
+        }))
+    }
+
+    /// Looks `uid` up in the passwd file.
+    pub fn user_name(&self, uid: u32) -> Option<String> {
+        let passwd = fs::read_to_string(self.path("/etc/passwd")).ok()?;
+        passwd.lines().find_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?;
+            (id.parse() == Ok(uid)).then(|| name.to_owned())
+        })
+    }
+}
+
+fn clock_ticks() -> u64 {
+    // SAFETY: sysconf has no preconditions.
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as u64,
+        _ => 100,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch root directory, removed again when dropped.
+    struct Fixture {
+        root: PathBuf,
+    }
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir()
+                .join(format!("services-gui-procfs-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            Self { root }
+        }
+
+        fn file(&self, path: &str, content: &str) -> &Self {
+            let path = self.root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+            self
+        }
+
+        fn sys_root(&self) -> SysRoot {
+            SysRoot::new(&self.root)
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    #[test]
+    fn cgroup_procs_includes_child_groups() {
+        let fixture = Fixture::new("cgroup");
+        fixture
+            .file(
+                "sys/fs/cgroup/system.slice/foo.service/cgroup.procs",
+                "42\n7\n",
+            )
+            .file(
+                "sys/fs/cgroup/system.slice/foo.service/worker/cgroup.procs",
+                "100\n",
+            )
+            .file("sys/fs/cgroup/system.slice/bar.service/cgroup.procs", "5\n");
+        let pids = fixture
+            .sys_root()
+            .cgroup_procs("/system.slice/foo.service")
+            .unwrap();
+        assert_eq!(pids, vec![7, 42, 100]);
+    }
+
+    #[test]
+    fn cgroup_procs_of_missing_group_fails() {
+        let fixture = Fixture::new("cgroup-missing");
+        assert!(fixture.sys_root().cgroup_procs("/gone.service").is_err());
+    }
+
+    #[test]
+    fn unit_of_pid_prefers_unified_hierarchy() {
+        let fixture = Fixture::new("unit-of-pid");
+        fixture
+            .file(
+                "proc/10/cgroup",
+                "1:name=systemd:/system.slice/old.service\n0::/system.slice/sshd.service\n",
+            )
+            .file(
+                "proc/11/cgroup",
+                "0::/user.slice/user-1000.slice/user@1000.service/app.slice/foo.scope\n",
+            )
+            .file(
+                "proc/12/cgroup",
+                "1:name=systemd:/system.slice/cron.service\n",
+            )
+            .file("proc/14/cgroup", "0::/\n");
+        let root = fixture.sys_root();
+        assert_eq!(
+            root.unit_of_pid(10).unwrap().as_deref(),
+            Some("sshd.service")
+        );
+        assert_eq!(root.unit_of_pid(11).unwrap().as_deref(), Some("foo.scope"));
+        assert_eq!(
+            root.unit_of_pid(12).unwrap().as_deref(),
+            Some("cron.service")
+        );
+        assert_eq!(root.unit_of_pid(14).unwrap(), None);
+        assert!(root.unit_of_pid(15).is_err());
+    }
+
+    #[test]
+    fn sockets_on_port_finds_listeners_in_all_tables() {
+        let fixture = Fixture::new("sockets");
+        let header = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n";
+        fixture
+            .file(
+                "proc/net/tcp",
+                &format!(
+                    "{header}   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1111 1 0000000000000000 100 0 0 10 0\n   1: 0A00000F:0016 0A000001:D431 01 00000000:00000000 02:00098E3B 00000000     0        0 4444 4 0000000000000000 20 4 31 10 -1\n   2: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 2222 1 0000000000000000 100 0 0 10 0\n"
+                ),
+            )
+            .file(
+                "proc/net/udp",
+                &format!(
+                    "{header}   0: 00000000:0016 00000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 0 2 0000000000000000 0\n"
+                ),
+            )
+            .file(
+                "proc/net/tcp6",
+                &format!(
+                    "{header}   0: 00000000000000000000000000000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 3333 1 0000000000000000 100 0 0 10 0\n"
+                ),
+            );
+        // There is no udp6 table, as without IPv6, and the established
+        // connection to port 22 isn't its owner.
+        let sockets = fixture.sys_root().sockets_on_port(22).unwrap();
+        assert_eq!(sockets, vec![("tcp", 1111), ("tcp6", 3333)]);
+        assert_eq!(
+            fixture.sys_root().sockets_on_port(631).unwrap(),
+            vec![("tcp", 2222)]
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Finds text in journal messages, for the search box of the journal window.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    Plain,
+    IgnoreCase,
+    Regex,
+}
+
+impl SearchMode {
+    pub const ALL: [SearchMode; 3] = [Self::Plain, Self::IgnoreCase, Self::Regex];
+}
+
+impl Display for SearchMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Plain => "plain",
+            Self::IgnoreCase => "ignore case",
+            Self::Regex => "regex",
+        })
+    }
+}
+
+/// Builds what finds `pattern`; every mode goes through a regex, so the
+/// match ranges are byte ranges into the message either way.
+pub fn matcher(pattern: &str, mode: SearchMode) -> Result<Regex, regex::Error> {
+    let pattern = match mode {
+        SearchMode::Regex => pattern.to_owned(),
+        SearchMode::Plain | SearchMode::IgnoreCase => regex::escape(pattern),
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(mode == SearchMode::IgnoreCase)
+        .build()
+}
+
+/// The rows with a match, and where in their message.
+#[derive(Debug, Default)]
+pub struct Hits {
+    /// In order, for going to the next and previous one.
+    pub rows: Vec<usize>,
+    pub ranges: HashMap<usize, Vec<Range<usize>>>,
+}
+
+/// Searches `messages`, one per row; slow on a full window, so it is meant
+/// to run on a blocking thread.
+pub fn search(matcher: &Regex, messages: &[String]) -> Hits {
+    let mut hits = Hits::default();
+    for (row, message) in messages.iter().enumerate() {
+        let ranges: Vec<_> = matcher
+            .find_iter(message)
+            .map(|found| found.range())
+            .filter(|range| !range.is_empty())
+            .collect();
+        if !ranges.is_empty() {
+            hits.rows.push(row);
+            hits.ranges.insert(row, ranges);
+        }
+    }
+    hits
+}
@@ -0,0 +1,418 @@
+//! Exposure scoring of a unit's sandboxing, modelled on
+//! `systemd-analyze security`.
+//!
+//! Every check has a weight and a range. Its badness goes from 0 (fully
+//! sandboxed) to the range, and the exposure is the weighted badness scaled
+//! to 0–10, so the more important settings count more.
+
+use std::collections::HashMap;
+
+use zvariant::OwnedObjectPath;
+
+use crate::systemd::{self, UNIT_INTERFACE};
+
+/// `CLONE_NEW*` flags of all the namespace types `RestrictNamespaces=` knows.
+const ALL_NAMESPACES: u64 = 0x7e02_0000;
+
+/// Capabilities that on their own are enough to take over the system:
+/// CAP_SYS_ADMIN, CAP_SYS_PTRACE, CAP_SYS_MODULE, CAP_SYS_RAWIO,
+/// CAP_DAC_OVERRIDE, CAP_SETUID, CAP_SETGID and CAP_NET_ADMIN.
+const DANGEROUS_CAPABILITIES: [u32; 8] = [21, 19, 16, 17, 1, 7, 6, 12];
+
+/// Settings that are either on or off, with the description and weight of
+/// their check.
+const BOOLEAN_CHECKS: [(&str, &str, u32); 15] = [
+    ("PrivateTmp", "Service has its own /tmp", 1000),
+    (
+        "PrivateDevices",
+        "Service has no access to hardware devices",
+        1000,
+    ),
+    (
+        "PrivateNetwork",
+        "Service has no access to the host's network",
+        2500,
+    ),
+    ("PrivateUsers", "Service doesn't see the host's users", 1000),
+    (
+        "ProtectKernelTunables",
+        "Service can't alter kernel tunables",
+        1000,
+    ),
+    (
+        "ProtectKernelModules",
+        "Service can't load kernel modules",
+        1000,
+    ),
+    (
+        "ProtectKernelLogs",
+        "Service can't read the kernel log ring buffer",
+        1000,
+    ),
+    (
+        "ProtectControlGroups",
+        "Service can't modify the control group tree",
+        1000,
+    ),
+    (
+        "ProtectClock",
+        "Service can't change the system clock",
+        1000,
+    ),
+    ("ProtectHostname", "Service can't change the hostname", 50),
+    (
+        "RestrictSUIDSGID",
+        "Service can't create SUID/SGID files",
+        1000,
+    ),
+    (
+        "RestrictRealtime",
+        "Service can't get realtime scheduling",
+        500,
+    ),
+    (
+        "MemoryDenyWriteExecute",
+        "Service can't create writable executable memory",
+        100,
+    ),
+    (
+        "LockPersonality",
+        "Service can't change the execution domain",
+        100,
+    ),
+    (
+        "RemoveIPC",
+        "Service's IPC objects are removed when it stops",
+        100,
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// The directive the check is about, e.g. `ProtectSystem`.
+    pub directive: &'static str,
+    pub description: &'static str,
+    pub weight: u32,
+    pub range: u32,
+    /// The drop-in line that brings the badness down to 0.
+    pub hardening: String,
+    /// None if the manager doesn't know the setting, in which case the check
+    /// doesn't count.
+    pub badness: Option<u32>,
+    /// The current value, as shown to the user.
+    pub value: String,
+}
+
+impl Check {
+    /// The share of the score this check is responsible for.
+    pub fn exposure(&self, total_weight: f64) -> f64 {
+        match self.badness {
+            Some(badness) => 10.0 * (self.weight * badness) as f64 / total_weight,
+            None => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Exposure {
+    pub checks: Vec<Check>,
+}
+
+impl Exposure {
+    /// The largest weighted badness the checks that count could have.
+    pub fn total_weight(&self) -> f64 {
+        self.checks
+            .iter()
+            .filter(|check| check.badness.is_some())
+            .map(|check| (check.weight * check.range) as f64)
+            .sum()
+    }
+
+    /// From 0.0 for a fully sandboxed unit to 10.0 for a wide open one.
+    pub fn score(&self) -> f64 {
+        let total = self.total_weight();
+        if total == 0.0 {
+            return 0.0;
+        }
+        self.checks.iter().map(|check| check.exposure(total)).sum()
+    }
+
+    /// The score if the checks for which `applied` returns true were
+    /// hardened, the rest staying as they are.
+    pub fn predicted<F>(&self, mut applied: F) -> f64
+    where
+        F: FnMut(usize, &Check) -> bool,
+    {
+        let mut predicted = self.clone();
+        for (index, check) in predicted.checks.iter_mut().enumerate() {
+            if check.badness.is_some() && applied(index, &self.checks[index]) {
+                check.badness = Some(0);
+            }
+        }
+        predicted.score()
+    }
+}
+
+/// Names a score the way `systemd-analyze security` does.
+pub fn level(score: f64) -> &'static str {
+    match score {
+        score if score < 1.0 => "PERFECT",
+        score if score < 2.0 => "SAFE",
+        score if score < 5.0 => "OK",
+        score if score < 7.0 => "MEDIUM",
+        score if score < 9.0 => "EXPOSED",
+        _ => "UNSAFE",
+    }
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_owned()
+}
+
+/// A boolean setting where `true` is the safe value.
+fn boolean(
+    directive: &'static str,
+    description: &'static str,
+    weight: u32,
+    value: Option<bool>,
+) -> Check {
+    Check {
+        directive,
+        description,
+        weight,
+        range: 1,
+        hardening: format!("{directive}=yes"),
+        badness: value.map(|value| u32::from(!value)),
+        value: value.map(yes_no).unwrap_or_default(),
+    }
+}
+
+/// Computes the exposure of `unit` from the sandboxing properties of its
+/// type interface, e.g. `org.freedesktop.systemd1.Service`.
+pub async fn exposure(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+    unit: String,
+) -> zbus::Result<Exposure> {
+    let interface = systemd::unit_type_interface(&unit).unwrap_or(UNIT_INTERFACE);
+    let proxy = systemd::unit_interface_proxy(&con, path, interface).await?;
+    let mut checks = Vec::new();
+
+    // Properties the manager doesn't have yet are left out of the score.
+    let bool_property = |name: &'static str| {
+        let proxy = &proxy;
+        async move { proxy.get_property::<bool>(name).await.ok() }
+    };
+
+    let user: Option<String> = proxy.get_property("User").await.ok();
+    let dynamic_user = bool_property("DynamicUser").await;
+    checks.push(Check {
+        directive: "User",
+        description: "Service runs as a dedicated user",
+        weight: 2000,
+        range: 10,
+        hardening: "DynamicUser=yes".to_owned(),
+        badness: user.as_ref().map(|user| {
+            if dynamic_user == Some(true) || !(user.is_empty() || user == "root" || user == "0") {
+                0
+            } else {
+                10
+            }
+        }),
+        value: match (&user, dynamic_user) {
+            (_, Some(true)) => "dynamic".to_owned(),
+            (Some(user), _) if user.is_empty() => "root".to_owned(),
+            (Some(user), _) => user.clone(),
+            (None, _) => String::new(),
+        },
+    });
+
+    checks.push(boolean(
+        "NoNewPrivileges",
+        "Service processes can't acquire new privileges",
+        1000,
+        bool_property("NoNewPrivileges").await,
+    ));
+
+    let capabilities: Option<u64> = proxy.get_property("CapabilityBoundingSet").await.ok();
+    checks.push(Check {
+        directive: "CapabilityBoundingSet",
+        description: "Service can't use the capabilities that allow taking over the system",
+        weight: 1500,
+        range: DANGEROUS_CAPABILITIES.len() as u32,
+        hardening: "CapabilityBoundingSet=".to_owned(),
+        badness: capabilities.map(|set| {
+            DANGEROUS_CAPABILITIES
+                .iter()
+                .filter(|capability| set & (1 << **capability) != 0)
+                .count() as u32
+        }),
+        value: capabilities
+            .map(|set| format!("{set:#x}"))
+            .unwrap_or_default(),
+    });
+
+    let protect_system: Option<String> = proxy.get_property("ProtectSystem").await.ok();
+    checks.push(Check {
+        directive: "ProtectSystem",
+        description: "Service has read-only access to the OS file hierarchy",
+        weight: 1000,
+        range: 10,
+        hardening: "ProtectSystem=strict".to_owned(),
+        badness: protect_system.as_deref().map(|value| match value {
+            "strict" => 0,
+            "full" => 3,
+            "yes" | "true" => 5,
+            _ => 10,
+        }),
+        value: protect_system.unwrap_or_default(),
+    });
+
+    let protect_home: Option<String> = proxy.get_property("ProtectHome").await.ok();
+    checks.push(Check {
+        directive: "ProtectHome",
+        description: "Service has no access to home directories",
+        weight: 1000,
+        range: 10,
+        hardening: "ProtectHome=yes".to_owned(),
+        badness: protect_home.as_deref().map(|value| match value {
+            "yes" | "true" | "tmpfs" => 0,
+            "read-only" => 5,
+            _ => 10,
+        }),
+        value: protect_home.unwrap_or_default(),
+    });
+
+    for (directive, description, weight) in BOOLEAN_CHECKS {
+        checks.push(boolean(
+            directive,
+            description,
+            weight,
+            bool_property(directive).await,
+        ));
+    }
+
+    let address_families: Option<(bool, Vec<String>)> =
+        proxy.get_property("RestrictAddressFamilies").await.ok();
+    checks.push(Check {
+        directive: "RestrictAddressFamilies",
+        description: "Service may only use the socket address families it needs",
+        weight: 1500,
+        range: 10,
+        hardening: "RestrictAddressFamilies=AF_UNIX AF_INET AF_INET6".to_owned(),
+        badness: address_families.as_ref().map(|(allow_list, families)| {
+            match (allow_list, families.is_empty()) {
+                // An empty allow list forbids every family.
+                (true, _) => 0,
+                (false, false) => 5,
+                (false, true) => 10,
+            }
+        }),
+        value: address_families
+            .map(|(allow_list, families)| {
+                format!(
+                    "{}{}",
+                    if allow_list { "" } else { "~" },
+                    families.join(" ")
+                )
+            })
+            .unwrap_or_default(),
+    });
+
+    let namespaces: Option<u64> = proxy.get_property("RestrictNamespaces").await.ok();
+    checks.push(Check {
+        directive: "RestrictNamespaces",
+        description: "Service can't create kernel namespaces",
+        weight: 1000,
+        range: 10,
+        hardening: "RestrictNamespaces=yes".to_owned(),
+        badness: namespaces.map(|allowed| match allowed & ALL_NAMESPACES {
+            0 => 0,
+            ALL_NAMESPACES => 10,
+            _ => 5,
+        }),
+        value: namespaces
+            .map(|allowed| format!("{allowed:#x}"))
+            .unwrap_or_default(),
+    });
+
+    let syscalls: Option<(bool, Vec<String>)> = proxy.get_property("SystemCallFilter").await.ok();
+    checks.push(Check {
+        directive: "SystemCallFilter",
+        description: "Service may only use the system calls it needs",
+        weight: 1000,
+        range: 10,
+        hardening: "SystemCallFilter=@system-service".to_owned(),
+        badness: syscalls.as_ref().map(|(allow_list, calls)| {
+            match (allow_list, calls.is_empty()) {
+                (true, _) => 0,
+                (false, false) => 5,
+                (false, true) => 10,
+            }
+        }),
+        value: syscalls
+            .map(|(allow_list, calls)| match calls.len() {
+                0 => String::new(),
+                count => format!("{}{count} calls", if allow_list { "" } else { "~" }),
+            })
+            .unwrap_or_default(),
+    });
+
+    let architectures: Option<Vec<String>> =
+        proxy.get_property("SystemCallArchitectures").await.ok();
+    checks.push(Check {
+        directive: "SystemCallArchitectures",
+        description: "Service may only use native system calls",
+        weight: 1000,
+        range: 10,
+        hardening: "SystemCallArchitectures=native".to_owned(),
+        badness: architectures.as_ref().map(|architectures| {
+            if architectures.iter().any(|a| a == "native") && architectures.len() == 1 {
+                0
+            } else {
+                10
+            }
+        }),
+        value: architectures
+            .map(|architectures| architectures.join(" "))
+            .unwrap_or_default(),
+    });
+
+    let denied: Option<Vec<(i32, Vec<u8>, u32)>> = proxy.get_property("IPAddressDeny").await.ok();
+    checks.push(Check {
+        directive: "IPAddressDeny",
+        description: "Service can't talk to arbitrary IP addresses",
+        weight: 1000,
+        range: 10,
+        hardening: "IPAddressDeny=any".to_owned(),
+        badness: denied.as_ref().map(|denied| {
+            // Denying the whole address space is what "any" expands to.
+            if denied.iter().any(|(_, _, prefix)| *prefix == 0) {
+                0
+            } else if denied.is_empty() {
+                10
+            } else {
+                5
+            }
+        }),
+        value: denied
+            .map(|denied| format!("{} ranges", denied.len()))
+            .unwrap_or_default(),
+    });
+
+    Ok(Exposure { checks })
+}
+
+/// Scores each of `units`, leaving out the ones that couldn't be read.
+pub async fn exposures(
+    con: zbus::Connection,
+    units: Vec<(String, OwnedObjectPath)>,
+) -> HashMap<String, f64> {
+    let mut scores = HashMap::new();
+    for (unit, path) in units {
+        if let Ok(exposure) = exposure(con.clone(), path, unit.clone()).await {
+            scores.insert(unit, exposure.score());
+        }
+    }
+    scores
+}
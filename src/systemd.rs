@@ -1,6 +1,8 @@
 use std::fmt::Display;
 use zvariant::OwnedObjectPath;
 
+use crate::format;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LoadState {
     Stub,
@@ -47,7 +49,7 @@ impl Display for LoadState {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ActiveState {
     Active,
     Reloading,
@@ -55,6 +57,12 @@ pub enum ActiveState {
     Failed,
     Activating,
     Deactivating,
+    /// Running a clean operation, see `Unit.Clean()`.
+    Maintenance,
+    /// Refreshing extensions or mounts of a running unit, since systemd 255.
+    Refreshing,
+    /// What a newer manager reported that isn't known here.
+    Unknown(String),
 }
 
 impl From<&str> for ActiveState {
@@ -66,7 +74,9 @@ impl From<&str> for ActiveState {
             "failed" => Self::Failed,
             "activating" => Self::Activating,
             "deactivating" => Self::Deactivating,
-            _ => todo!(),
+            "maintenance" => Self::Maintenance,
+            "refreshing" => Self::Refreshing,
+            _ => Self::Unknown(value.to_owned()),
         }
     }
 }
@@ -86,16 +96,74 @@ impl Display for ActiveState {
             Self::Failed => write!(f, "failed"),
             Self::Activating => write!(f, "activating"),
             Self::Deactivating => write!(f, "deactivating"),
+            Self::Maintenance => write!(f, "maintenance"),
+            Self::Refreshing => write!(f, "refreshing"),
+            Self::Unknown(state) => write!(f, "{state}"),
         }
     }
 }
 
 impl ActiveState {
-    pub fn can_start(self) -> bool {
+    pub fn can_start(&self) -> bool {
         matches!(self, Self::Inactive | Self::Failed)
     }
 }
 
+/// The cgroup freezer state of a unit, see `FreezerState=` in
+/// org.freedesktop.systemd1(5).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FreezerState {
+    Running,
+    Freezing,
+    Frozen,
+    Thawing,
+    /// Frozen along with a parent slice, since systemd 256.
+    FreezingByParent,
+    FrozenByParent,
+    /// What a newer manager reported that isn't known here.
+    Unknown(String),
+}
+
+impl From<&str> for FreezerState {
+    fn from(value: &str) -> Self {
+        match value {
+            "running" => Self::Running,
+            "freezing" => Self::Freezing,
+            "frozen" => Self::Frozen,
+            "thawing" => Self::Thawing,
+            "freezing-by-parent" => Self::FreezingByParent,
+            "frozen-by-parent" => Self::FrozenByParent,
+            _ => Self::Unknown(value.to_owned()),
+        }
+    }
+}
+
+impl From<String> for FreezerState {
+    fn from(value: String) -> Self {
+        Self::from(value.as_ref())
+    }
+}
+
+impl Display for FreezerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Running => write!(f, "running"),
+            Self::Freezing => write!(f, "freezing"),
+            Self::Frozen => write!(f, "frozen"),
+            Self::Thawing => write!(f, "thawing"),
+            Self::FreezingByParent => write!(f, "freezing-by-parent"),
+            Self::FrozenByParent => write!(f, "frozen-by-parent"),
+            Self::Unknown(state) => write!(f, "{state}"),
+        }
+    }
+}
+
+impl FreezerState {
+    pub fn can_freeze(&self) -> bool {
+        matches!(self, Self::Running | Self::Thawing)
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum UnitFileState {
     Enabled,
@@ -217,3 +285,878 @@ pub async fn list_units(con: zbus::Connection) -> zbus::Result<Vec<UnitData>> {
 
     Ok(units)
 }
+
+/// The manager's overall state, as shown by `systemctl is-system-running`.
+#[derive(Debug, Clone)]
+pub struct SystemHealth {
+    /// e.g. `running`, `degraded` or `maintenance`.
+    pub state: String,
+    pub failed_units: u32,
+}
+
+pub async fn system_health(con: zbus::Connection) -> zbus::Result<SystemHealth> {
+    let manager = zbus_systemd::systemd1::ManagerProxy::new(&con).await?;
+    Ok(SystemHealth {
+        state: manager.system_state().await?,
+        failed_units: manager.n_failed_units().await?,
+    })
+}
+
+/// Lists the failed units of every type, not only services.
+pub async fn list_failed_units(con: zbus::Connection) -> zbus::Result<Vec<UnitData>> {
+    let manager = zbus_systemd::systemd1::ManagerProxy::new(&con).await?;
+    let mut units = manager
+        .list_units_by_patterns(vec!["failed".to_owned()], Vec::new())
+        .await?
+        .iter()
+        .map(UnitData::from)
+        .collect::<Vec<UnitData>>();
+    units.sort_by_key(|u| u.name.clone());
+
+    Ok(units)
+}
+
+/// Resets the failed state of `units`, stopping at the first error.
+pub async fn reset_failed_units(con: zbus::Connection, units: Vec<String>) -> zbus::Result<()> {
+    let manager = zbus_systemd::systemd1::ManagerProxy::new(&con).await?;
+    for unit in units {
+        manager.reset_failed_unit(unit).await?;
+    }
+    Ok(())
+}
+
+/// Queues a restart of each of `units`, stopping at the first error.
+pub async fn restart_units(con: zbus::Connection, units: Vec<String>) -> zbus::Result<()> {
+    let manager = zbus_systemd::systemd1::ManagerProxy::new(&con).await?;
+    for unit in units {
+        manager.restart_unit(unit, "replace".to_owned()).await?;
+    }
+    Ok(())
+}
+
+pub const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+
+/// Returns the type-specific D-Bus interface of `unit`, e.g.
+/// `org.freedesktop.systemd1.Service` for `sshd.service`.
+pub fn unit_type_interface(unit: &str) -> Option<&'static str> {
+    let (_, suffix) = unit.rsplit_once('.')?;
+    Some(match suffix {
+        "service" => "org.freedesktop.systemd1.Service",
+        "socket" => "org.freedesktop.systemd1.Socket",
+        "target" => "org.freedesktop.systemd1.Target",
+        "device" => "org.freedesktop.systemd1.Device",
+        "mount" => "org.freedesktop.systemd1.Mount",
+        "automount" => "org.freedesktop.systemd1.Automount",
+        "swap" => "org.freedesktop.systemd1.Swap",
+        "timer" => "org.freedesktop.systemd1.Timer",
+        "path" => "org.freedesktop.systemd1.Path",
+        "slice" => "org.freedesktop.systemd1.Slice",
+        "scope" => "org.freedesktop.systemd1.Scope",
+        _ => return None,
+    })
+}
+
+/// Whether units of this type live in their own control group, and so carry
+/// the resource-control properties.
+pub fn has_cgroup(unit: &str) -> bool {
+    matches!(
+        unit.rsplit_once('.').map(|(_, suffix)| suffix),
+        Some("service" | "socket" | "mount" | "swap" | "slice" | "scope")
+    )
+}
+
+/// Builds an uncached proxy for `interface` on the unit object at `path`, for
+/// the properties and methods not covered by zbus_systemd.
+///
+/// NOTE: caching is off because systemd doesn't emit change signals for most
+/// of the runtime properties (e.g. `MemoryCurrent`).
+pub async fn unit_interface_proxy(
+    con: &zbus::Connection,
+    path: OwnedObjectPath,
+    interface: &'static str,
+) -> zbus::Result<zbus::Proxy<'static>> {
+    zbus::ProxyBuilder::new_bare(con)
+        .destination("org.freedesktop.systemd1")?
+        .path(path)?
+        .interface(interface)?
+        .cache_properties(zbus::CacheProperties::No)
+        .build()
+        .await
+}
+
+/// A proxy for the manager interface, for the properties and methods the
+/// generated `ManagerProxy` may not have.
+pub async fn manager_proxy(con: &zbus::Connection) -> zbus::Result<zbus::Proxy<'static>> {
+    zbus::ProxyBuilder::new_bare(con)
+        .destination("org.freedesktop.systemd1")?
+        .path("/org/freedesktop/systemd1")?
+        .interface("org.freedesktop.systemd1.Manager")?
+        .cache_properties(zbus::CacheProperties::No)
+        .build()
+        .await
+}
+
+/// How the value of a resource-control property is written and shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    /// A byte size, `UINT64_MAX` meaning "infinity".
+    Bytes,
+    /// CPU time per second of wall clock time, in microseconds, shown as a
+    /// percentage like `CPUQuota=` does.
+    Percent,
+    /// A cgroup weight (1-10000), `UINT64_MAX` meaning "use the default".
+    Weight,
+    /// A plain count, `UINT64_MAX` meaning "infinity".
+    Count,
+}
+
+impl ResourceKind {
+    pub fn format(self, value: u64) -> String {
+        match self {
+            Self::Bytes => format::bytes(value),
+            Self::Percent => format::percent(value),
+            Self::Weight => match value {
+                u64::MAX => "default".to_owned(),
+                weight => weight.to_string(),
+            },
+            Self::Count => match value {
+                u64::MAX => "infinity".to_owned(),
+                count => count.to_string(),
+            },
+        }
+    }
+
+    pub fn parse(self, value: &str) -> Result<u64, &'static str> {
+        let value = value.trim();
+        match self {
+            Self::Bytes => format::parse_bytes(value),
+            Self::Percent => format::parse_percent(value),
+            Self::Weight => {
+                if value.is_empty() || value == "default" {
+                    return Ok(u64::MAX);
+                }
+                match value.parse() {
+                    Ok(weight @ 1..=10000) => Ok(weight),
+                    _ => Err("weight must be between 1 and 10000"),
+                }
+            }
+            Self::Count => {
+                if value.is_empty() || value == "infinity" {
+                    return Ok(u64::MAX);
+                }
+                value.parse().map_err(|_| "invalid number")
+            }
+        }
+    }
+}
+
+/// A resource-control setting that can be changed on a running unit through
+/// `SetUnitProperties`.
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceSetting {
+    /// The unit file directive, shown to the user.
+    pub directive: &'static str,
+    /// The D-Bus property holding the value.
+    pub property: &'static str,
+    pub kind: ResourceKind,
+}
+
+pub const RESOURCE_SETTINGS: [ResourceSetting; 9] = [
+    ResourceSetting {
+        directive: "MemoryMin",
+        property: "MemoryMin",
+        kind: ResourceKind::Bytes,
+    },
+    ResourceSetting {
+        directive: "MemoryLow",
+        property: "MemoryLow",
+        kind: ResourceKind::Bytes,
+    },
+    ResourceSetting {
+        directive: "MemoryHigh",
+        property: "MemoryHigh",
+        kind: ResourceKind::Bytes,
+    },
+    ResourceSetting {
+        directive: "MemoryMax",
+        property: "MemoryMax",
+        kind: ResourceKind::Bytes,
+    },
+    ResourceSetting {
+        directive: "MemorySwapMax",
+        property: "MemorySwapMax",
+        kind: ResourceKind::Bytes,
+    },
+    ResourceSetting {
+        directive: "CPUQuota",
+        property: "CPUQuotaPerSecUSec",
+        kind: ResourceKind::Percent,
+    },
+    ResourceSetting {
+        directive: "CPUWeight",
+        property: "CPUWeight",
+        kind: ResourceKind::Weight,
+    },
+    ResourceSetting {
+        directive: "IOWeight",
+        property: "IOWeight",
+        kind: ResourceKind::Weight,
+    },
+    ResourceSetting {
+        directive: "TasksMax",
+        property: "TasksMax",
+        kind: ResourceKind::Count,
+    },
+];
+
+/// Reads the values of [`RESOURCE_SETTINGS`], in order.
+pub async fn resource_limits(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+    interface: &'static str,
+) -> zbus::Result<Vec<u64>> {
+    let proxy = unit_interface_proxy(&con, path, interface).await?;
+    let mut values = Vec::with_capacity(RESOURCE_SETTINGS.len());
+    for setting in RESOURCE_SETTINGS {
+        values.push(proxy.get_property::<u64>(setting.property).await?);
+    }
+    Ok(values)
+}
+
+/// Changes resource-control properties of `unit`, only until the next reboot
+/// if `runtime` is set, or persistently through a drop-in otherwise.
+pub async fn set_resource_limits(
+    con: zbus::Connection,
+    unit: String,
+    runtime: bool,
+    values: Vec<(&'static str, u64)>,
+) -> zbus::Result<()> {
+    let properties = values
+        .into_iter()
+        .map(|(property, value)| (property.to_owned(), value.into()))
+        .collect();
+    zbus_systemd::systemd1::ManagerProxy::new(&con)
+        .await?
+        .set_unit_properties(unit, runtime, properties)
+        .await
+}
+
+/// Resource usage of a unit's control group, `UINT64_MAX` meaning the
+/// accounting for it is disabled.
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceUsage {
+    pub memory: u64,
+    pub tasks: u64,
+    pub cpu_nsec: u64,
+}
+
+pub async fn resource_usage(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+    interface: &'static str,
+) -> zbus::Result<ResourceUsage> {
+    let proxy = unit_interface_proxy(&con, path, interface).await?;
+    Ok(ResourceUsage {
+        memory: proxy.get_property("MemoryCurrent").await?,
+        tasks: proxy.get_property("TasksCurrent").await?,
+        cpu_nsec: proxy.get_property("CPUUsageNSec").await?,
+    })
+}
+
+/// Lists the processes of a unit as `(control group, PID, command line)`.
+pub async fn unit_processes(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+    interface: &'static str,
+) -> zbus::Result<Vec<(String, u32, String)>> {
+    unit_interface_proxy(&con, path, interface)
+        .await?
+        .call("GetProcesses", &())
+        .await
+}
+
+pub async fn control_group(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+    interface: &'static str,
+) -> zbus::Result<String> {
+    unit_interface_proxy(&con, path, interface)
+        .await?
+        .get_property("ControlGroup")
+        .await
+}
+
+/// Asks the manager which unit `pid` belongs to, returning its name.
+pub async fn unit_by_pid(con: zbus::Connection, pid: u32) -> zbus::Result<String> {
+    let path = zbus_systemd::systemd1::ManagerProxy::new(&con)
+        .await?
+        .get_unit_by_pid(pid)
+        .await?;
+    zbus_systemd::systemd1::UnitProxy::new(&con, path)
+        .await?
+        .id()
+        .await
+}
+
+/// First manager version having `Unit.Clean()`.
+pub const CLEAN_SINCE: u32 = 243;
+/// First manager version having `Unit.Freeze()`, `Unit.Thaw()` and the
+/// `FreezerState` property.
+pub const FREEZE_SINCE: u32 = 246;
+/// First manager version having `Manager.EnqueueUnitJob()`.
+pub const ENQUEUE_JOB_SINCE: u32 = 242;
+
+/// Returns the major version of the manager, e.g. 253 for "253.5-1-arch".
+pub async fn manager_version(con: zbus::Connection) -> zbus::Result<u32> {
+    let version = zbus_systemd::systemd1::ManagerProxy::new(&con)
+        .await?
+        .version()
+        .await?;
+    let digits: String = version
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits
+        .parse()
+        .map_err(|_| zbus::Error::Failure(format!("unknown manager version {version}")))
+}
+
+/// What `Unit.Clean()` can wipe, with the directive naming the directories
+/// that go with it.
+pub const CLEAN_MASKS: [(&str, &str); 5] = [
+    ("runtime", "RuntimeDirectory"),
+    ("state", "StateDirectory"),
+    ("cache", "CacheDirectory"),
+    ("logs", "LogsDirectory"),
+    ("configuration", "ConfigurationDirectory"),
+];
+
+/// Returns the directories each of [`CLEAN_MASKS`] would wipe, in order.
+pub async fn clean_directories(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+    interface: &'static str,
+) -> zbus::Result<Vec<Vec<String>>> {
+    let proxy = unit_interface_proxy(&con, path, interface).await?;
+    let mut directories = Vec::with_capacity(CLEAN_MASKS.len());
+    for (_, directive) in CLEAN_MASKS {
+        directories.push(proxy.get_property(directive).await?);
+    }
+    Ok(directories)
+}
+
+/// One `Condition*=` or `Assert*=` check, see the `Conditions` property.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    /// e.g. `ConditionPathExists`
+    pub kind: String,
+    /// Whether it's a triggering condition, written as `|`.
+    pub trigger: bool,
+    /// Whether it's negated, written as `!`.
+    pub negate: bool,
+    pub parameter: String,
+    /// Positive if it passed, negative if it failed, 0 if it wasn't checked.
+    pub state: i32,
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}={}{}{}",
+            self.kind,
+            if self.trigger { "|" } else { "" },
+            if self.negate { "!" } else { "" },
+            self.parameter
+        )
+    }
+}
+
+/// What's known about why a unit failed. The fields only some unit types
+/// have are `None` for the others.
+#[derive(Debug, Clone)]
+pub struct FailureInfo {
+    pub active_state: ActiveState,
+    /// e.g. `exit-code`, `timeout` or `start-limit-hit`.
+    pub result: Option<String>,
+    pub exec_main: Option<(i32, i32)>,
+    pub restarts: Option<u32>,
+    pub failed_conditions: Vec<Condition>,
+    pub failed_asserts: Vec<Condition>,
+    pub start_limit_burst: u32,
+    pub start_limit_interval_usec: u64,
+    /// The invocation ID in hex, as used by the journal, empty if the unit
+    /// never ran.
+    pub invocation: String,
+}
+
+impl FailureInfo {
+    pub fn hit_start_limit(&self) -> bool {
+        self.result.as_deref() == Some("start-limit-hit")
+    }
+}
+
+type ConditionTuple = (String, bool, bool, String, i32);
+
+fn failed_conditions(conditions: Vec<ConditionTuple>) -> Vec<Condition> {
+    conditions
+        .into_iter()
+        .map(|(kind, trigger, negate, parameter, state)| Condition {
+            kind,
+            trigger,
+            negate,
+            parameter,
+            state,
+        })
+        .filter(|condition| condition.state < 0)
+        .collect()
+}
+
+pub async fn failure_info(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+    unit: String,
+) -> zbus::Result<FailureInfo> {
+    let proxy = unit_interface_proxy(&con, path.clone(), UNIT_INTERFACE).await?;
+    let active_state: String = proxy.get_property("ActiveState").await?;
+    let conditions: Vec<ConditionTuple> = proxy.get_property("Conditions").await?;
+    let asserts: Vec<ConditionTuple> = proxy.get_property("Asserts").await?;
+    let invocation: Vec<u8> = proxy.get_property("InvocationID").await?;
+
+    let mut info = FailureInfo {
+        active_state: active_state.into(),
+        result: None,
+        exec_main: None,
+        restarts: None,
+        failed_conditions: failed_conditions(conditions),
+        failed_asserts: failed_conditions(asserts),
+        start_limit_burst: proxy.get_property("StartLimitBurst").await?,
+        start_limit_interval_usec: proxy.get_property("StartLimitIntervalUSec").await?,
+        invocation: invocation.iter().map(|b| format!("{b:02x}")).collect(),
+    };
+
+    if let Some(interface) = unit_type_interface(&unit) {
+        let proxy = unit_interface_proxy(&con, path, interface).await?;
+        // Not every unit type has these, e.g. targets have no Result.
+        info.result = proxy.get_property("Result").await.ok();
+        info.restarts = proxy.get_property("NRestarts").await.ok();
+        if let (Ok(code), Ok(status)) = (
+            proxy.get_property("ExecMainCode").await,
+            proxy.get_property("ExecMainStatus").await,
+        ) {
+            info.exec_main = Some((code, status));
+        }
+    }
+    Ok(info)
+}
+
+/// The dependency properties of a unit, each followed by the one holding the
+/// reverse relation, see org.freedesktop.systemd1(5).
+pub const DEPENDENCY_PROPERTIES: [&str; 12] = [
+    "Requires",
+    "RequiredBy",
+    "Wants",
+    "WantedBy",
+    "BindsTo",
+    "BoundBy",
+    "PartOf",
+    "ConsistsOf",
+    "Before",
+    "After",
+    "Triggers",
+    "TriggeredBy",
+];
+
+#[derive(Debug, Clone)]
+pub struct UnitDependencies {
+    pub active_state: ActiveState,
+    /// The units listed in each of [`DEPENDENCY_PROPERTIES`], in that order.
+    pub dependencies: Vec<Vec<String>>,
+}
+
+/// Reads the dependencies of `unit`, loading it if needed so that units which
+/// are only referenced can be shown as well.
+pub async fn unit_dependencies(
+    con: zbus::Connection,
+    unit: String,
+) -> zbus::Result<UnitDependencies> {
+    let manager = zbus_systemd::systemd1::ManagerProxy::new(&con).await?;
+    let path = manager.load_unit(unit).await?;
+    let proxy = unit_interface_proxy(&con, path, UNIT_INTERFACE).await?;
+
+    let active_state: String = proxy.get_property("ActiveState").await?;
+    let mut dependencies = Vec::with_capacity(DEPENDENCY_PROPERTIES.len());
+    for property in DEPENDENCY_PROPERTIES {
+        dependencies.push(proxy.get_property::<Vec<String>>(property).await?);
+    }
+    Ok(UnitDependencies {
+        active_state: active_state.into(),
+        dependencies,
+    })
+}
+
+/// The reverse dependencies along which a stop job propagates, the same ones
+/// the manager follows when it builds the stop transaction.
+const STOP_PROPAGATES_FROM: [&str; 4] = ["RequiredBy", "RequisiteOf", "BoundBy", "ConsistsOf"];
+
+/// Lists the units that would be stopped along with `unit`, with their
+/// current state, by following the reverse `Requires=`, `Requisite=`,
+/// `BindsTo=` and `PartOf=` dependencies transitively.
+pub async fn stop_impact(
+    con: zbus::Connection,
+    unit: String,
+) -> zbus::Result<Vec<(String, ActiveState)>> {
+    let manager = zbus_systemd::systemd1::ManagerProxy::new(&con).await?;
+    let mut seen = std::collections::HashSet::from([unit.clone()]);
+    let mut pending = vec![unit.clone()];
+    let mut affected = Vec::new();
+    while let Some(current) = pending.pop() {
+        let path = manager.load_unit(current.clone()).await?;
+        let proxy = unit_interface_proxy(&con, path, UNIT_INTERFACE).await?;
+        for property in STOP_PROPAGATES_FROM {
+            for dependent in proxy.get_property::<Vec<String>>(property).await? {
+                if seen.insert(dependent.clone()) {
+                    pending.push(dependent);
+                }
+            }
+        }
+        if current != unit {
+            let state: String = proxy.get_property("ActiveState").await?;
+            affected.push((current, state.into()));
+        }
+    }
+    affected.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(affected)
+}
+
+/// Stops `unit` through `Manager.EnqueueUnitJob()`, returning the
+/// `(unit, job type)` of every job the transaction queued besides its own.
+pub async fn enqueue_stop(
+    con: zbus::Connection,
+    unit: String,
+) -> zbus::Result<Vec<(String, String)>> {
+    type AffectedJob = (u32, OwnedObjectPath, String, OwnedObjectPath, String);
+    let (_, _, _, _, _, affected): (
+        u32,
+        OwnedObjectPath,
+        String,
+        OwnedObjectPath,
+        String,
+        Vec<AffectedJob>,
+    ) = manager_proxy(&con)
+        .await?
+        .call("EnqueueUnitJob", &(unit, "stop", "replace"))
+        .await?;
+    Ok(affected
+        .into_iter()
+        .map(|(_, _, unit, _, job_type)| (unit, job_type))
+        .collect())
+}
+
+/// How long each boot phase took, in microseconds, computed the same way as
+/// `systemd-analyze time` does from the manager's monotonic timestamps.
+#[derive(Debug, Clone, Copy)]
+pub struct BootTimes {
+    /// Both are zero unless the boot loader reports them.
+    pub firmware: u64,
+    pub loader: u64,
+    pub kernel: u64,
+    /// Zero when booting without an initrd.
+    pub initrd: u64,
+    pub userspace: u64,
+    /// Where userspace started on the monotonic clock, what the unit times
+    /// are shown relative to.
+    pub userspace_start: u64,
+    /// False while the boot is still going on, in which case the userspace
+    /// time isn't known yet.
+    pub finished: bool,
+}
+
+impl BootTimes {
+    pub fn total(&self) -> u64 {
+        self.firmware + self.loader + self.kernel + self.initrd + self.userspace
+    }
+}
+
+pub async fn boot_times(con: zbus::Connection) -> zbus::Result<BootTimes> {
+    let manager = manager_proxy(&con).await?;
+    // The firmware and loader timestamps count back from the kernel start,
+    // which is zero on the monotonic clock.
+    let firmware: u64 = manager.get_property("FirmwareTimestampMonotonic").await?;
+    let loader: u64 = manager.get_property("LoaderTimestampMonotonic").await?;
+    let initrd: u64 = manager.get_property("InitRDTimestampMonotonic").await?;
+    let userspace: u64 = manager.get_property("UserspaceTimestampMonotonic").await?;
+    let finish: u64 = manager.get_property("FinishTimestampMonotonic").await?;
+
+    Ok(BootTimes {
+        firmware: firmware.saturating_sub(loader),
+        loader,
+        kernel: if initrd > 0 { initrd } else { userspace },
+        initrd: if initrd > 0 {
+            userspace.saturating_sub(initrd)
+        } else {
+            0
+        },
+        userspace: finish.saturating_sub(userspace),
+        userspace_start: userspace,
+        finished: finish > 0,
+    })
+}
+
+/// When a unit went through its states, as monotonic timestamps in
+/// microseconds, zero for what didn't happen (yet).
+#[derive(Debug, Clone)]
+pub struct UnitTimes {
+    pub name: String,
+    pub active_state: ActiveState,
+    /// Left the inactive state, i.e. started activating.
+    pub activating: u64,
+    pub activated: u64,
+    pub deactivating: u64,
+    pub deactivated: u64,
+    pub after: Vec<String>,
+}
+
+impl UnitTimes {
+    /// How long the unit took to start, the way `systemd-analyze blame`
+    /// counts it.
+    pub fn activation_time(&self) -> Option<u64> {
+        (self.activating > 0 && self.activated >= self.activating)
+            .then(|| self.activated - self.activating)
+    }
+}
+
+/// Reads the state change timestamps of every loaded unit.
+pub async fn unit_times(con: zbus::Connection) -> zbus::Result<Vec<UnitTimes>> {
+    let manager = zbus_systemd::systemd1::ManagerProxy::new(&con).await?;
+    let mut times = Vec::new();
+    for unit in manager.list_units().await?.iter().map(UnitData::from) {
+        // A unit that got unloaded since it was listed is left out.
+        if let Ok(unit_times) = read_unit_times(&con, unit).await {
+            times.push(unit_times);
+        }
+    }
+    Ok(times)
+}
+
+async fn read_unit_times(con: &zbus::Connection, unit: UnitData) -> zbus::Result<UnitTimes> {
+    let proxy = unit_interface_proxy(con, unit.object_path, UNIT_INTERFACE).await?;
+    Ok(UnitTimes {
+        name: unit.name,
+        active_state: unit.active_status,
+        activating: proxy.get_property("InactiveExitTimestampMonotonic").await?,
+        activated: proxy.get_property("ActiveEnterTimestampMonotonic").await?,
+        deactivating: proxy.get_property("ActiveExitTimestampMonotonic").await?,
+        deactivated: proxy
+            .get_property("InactiveEnterTimestampMonotonic")
+            .await?,
+        after: proxy.get_property("After").await?,
+    })
+}
+
+/// The invocation ID of the unit's current or last run, in hex as used by
+/// the journal, empty if it never ran.
+pub async fn invocation_id(con: zbus::Connection, path: OwnedObjectPath) -> zbus::Result<String> {
+    let id: Vec<u8> = unit_interface_proxy(&con, path, UNIT_INTERFACE)
+        .await?
+        .get_property("InvocationID")
+        .await?;
+    Ok(id.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Where the administrator's drop-ins for `unit` go, telling the user
+/// manager's units apart by where their unit file lives.
+pub async fn drop_in_dir(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+    unit: String,
+) -> zbus::Result<std::path::PathBuf> {
+    let fragment: String = unit_interface_proxy(&con, path, UNIT_INTERFACE)
+        .await?
+        .get_property("FragmentPath")
+        .await?;
+    let base = if fragment.contains("/systemd/user/") {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(std::path::PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config"))
+            })
+            .ok_or_else(|| zbus::Error::Failure("no configuration directory".to_owned()))?
+            .join("systemd/user")
+    } else {
+        std::path::PathBuf::from("/etc/systemd/system")
+    };
+    Ok(base.join(format!("{unit}.d")))
+}
+
+/// Makes the manager pick up changed unit files, then restarts `unit`.
+pub async fn reload_and_restart(con: zbus::Connection, unit: String) -> zbus::Result<()> {
+    let manager = zbus_systemd::systemd1::ManagerProxy::new(&con).await?;
+    manager.reload().await?;
+    manager.restart_unit(unit, "replace".to_owned()).await?;
+    Ok(())
+}
+
+pub async fn unit_active_state(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+) -> zbus::Result<ActiveState> {
+    let state: String = unit_interface_proxy(&con, path, UNIT_INTERFACE)
+        .await?
+        .get_property("ActiveState")
+        .await?;
+    Ok(state.into())
+}
+
+/// A property as read through `org.freedesktop.DBus.Properties.GetAll`, with
+/// its value already decoded for display.
+#[derive(Debug, Clone)]
+pub struct RawProperty {
+    pub name: String,
+    pub signature: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct InterfaceProperties {
+    pub interface: String,
+    pub properties: Vec<RawProperty>,
+}
+
+/// Reads every property of every interface the unit object implements, as
+/// found by introspecting it.
+pub async fn all_properties(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+) -> zbus::Result<Vec<InterfaceProperties>> {
+    let xml = zbus::fdo::IntrospectableProxy::builder(&con)
+        .destination("org.freedesktop.systemd1")?
+        .path(path.clone())?
+        .build()
+        .await?
+        .introspect()
+        .await?;
+    let properties = zbus::fdo::PropertiesProxy::builder(&con)
+        .destination("org.freedesktop.systemd1")?
+        .path(path)?
+        .build()
+        .await?;
+
+    let mut all = Vec::new();
+    // NOTE: only the interface names are needed, which doesn't warrant an
+    // XML parser.
+    for interface in xml
+        .split("<interface name=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('"').map(|(name, _)| name))
+        .filter(|name| !name.starts_with("org.freedesktop.DBus."))
+    {
+        let values = properties
+            .get_all(zbus::names::InterfaceName::try_from(interface)?)
+            .await?;
+        let mut properties: Vec<RawProperty> = values
+            .iter()
+            .map(|(name, value)| RawProperty {
+                name: name.clone(),
+                signature: value.value_signature().to_string(),
+                value: format::value(name, value),
+            })
+            .collect();
+        properties.sort_by(|a, b| a.name.cmp(&b.name));
+        all.push(InterfaceProperties {
+            interface: interface.to_owned(),
+            properties,
+        });
+    }
+    Ok(all)
+}
+
+/// The properties of a service holding the commands it runs, in the order
+/// it runs them.
+pub const EXEC_PROPERTIES: [&str; 6] = [
+    "ExecStartPre",
+    "ExecStart",
+    "ExecStartPost",
+    "ExecReload",
+    "ExecStop",
+    "ExecStopPost",
+];
+
+/// One command of an `Exec*=` setting, with how its last run went.
+#[derive(Debug, Clone)]
+pub struct ExecCommand {
+    pub path: String,
+    pub argv: Vec<String>,
+    /// Set by the `-` prefix, a failure of the command is then ignored.
+    pub ignore_failure: bool,
+    /// Wall clock timestamps in microseconds, 0 if it never started or exited.
+    pub started: u64,
+    pub exited: u64,
+    pub pid: u32,
+    pub code: i32,
+    pub status: i32,
+}
+
+type ExecTuple = (String, Vec<String>, bool, u64, u64, u64, u64, u32, i32, i32);
+
+/// How a service is launched, see org.freedesktop.systemd1(5).
+#[derive(Debug, Clone)]
+pub struct ExecDetails {
+    /// The commands of each of [`EXEC_PROPERTIES`], in order.
+    pub commands: Vec<Vec<ExecCommand>>,
+    pub user: String,
+    pub group: String,
+    pub working_directory: String,
+    /// `NAME=value` assignments.
+    pub environment: Vec<String>,
+    /// The files, and whether they may be missing (the `-` prefix).
+    pub environment_files: Vec<(String, bool)>,
+    pub service_type: String,
+    pub restart: String,
+    pub main_pid: u32,
+    /// What the service last sent as `STATUS=` through sd_notify(3).
+    pub status_text: String,
+}
+
+pub async fn exec_details(
+    con: zbus::Connection,
+    path: OwnedObjectPath,
+) -> zbus::Result<ExecDetails> {
+    let proxy = unit_interface_proxy(&con, path, "org.freedesktop.systemd1.Service").await?;
+    let mut commands = Vec::with_capacity(EXEC_PROPERTIES.len());
+    for property in EXEC_PROPERTIES {
+        let tuples: Vec<ExecTuple> = proxy.get_property(property).await?;
+        commands.push(
+            tuples
+                .into_iter()
+                .map(
+                    |(path, argv, ignore_failure, started, _, exited, _, pid, code, status)| {
+                        ExecCommand {
+                            path,
+                            argv,
+                            ignore_failure,
+                            started,
+                            exited,
+                            pid,
+                            code,
+                            status,
+                        }
+                    },
+                )
+                .collect(),
+        );
+    }
+    Ok(ExecDetails {
+        commands,
+        user: proxy.get_property("User").await?,
+        group: proxy.get_property("Group").await?,
+        working_directory: proxy.get_property("WorkingDirectory").await?,
+        environment: proxy.get_property("Environment").await?,
+        environment_files: proxy.get_property("EnvironmentFiles").await?,
+        service_type: proxy.get_property("Type").await?,
+        restart: proxy.get_property("Restart").await?,
+        main_pid: proxy.get_property("MainPID").await?,
+        status_text: proxy.get_property("StatusText").await?,
+    })
+}
@@ -0,0 +1,78 @@
+use egui::{Color32, Ui};
+use poll_promise::Promise;
+
+use crate::systemd::{self, InterfaceProperties, UnitData};
+
+/// Every property of a unit straight from D-Bus, for what the rest of the
+/// window doesn't show.
+#[derive(Default)]
+pub struct AllProperties {
+    unit: Option<String>,
+    interfaces: Option<zbus::Result<Vec<InterfaceProperties>>>,
+    search: String,
+}
+
+impl AllProperties {
+    pub fn draw(&mut self, ui: &mut Ui, con: &zbus::Connection, unit: &UnitData) {
+        let mut refresh = self.unit.as_deref() != Some(unit.name.as_str());
+        ui.horizontal(|ui| {
+            refresh |= ui.button("Refresh").clicked();
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.search);
+        });
+        if refresh {
+            self.unit = Some(unit.name.clone());
+            self.interfaces = Some(
+                Promise::spawn_async(systemd::all_properties(
+                    con.clone(),
+                    unit.object_path.clone(),
+                ))
+                .block_and_take(),
+            );
+        }
+
+        let interfaces = match &self.interfaces {
+            Some(Ok(interfaces)) => interfaces,
+            Some(Err(err)) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                return;
+            }
+            None => return,
+        };
+
+        let search = self.search.to_lowercase();
+        egui::ScrollArea::vertical()
+            .id_source("all_properties")
+            .max_height(384.0)
+            .show(ui, |ui| {
+                for interface in interfaces {
+                    let properties: Vec<_> = interface
+                        .properties
+                        .iter()
+                        .filter(|property| {
+                            property.name.to_lowercase().contains(&search)
+                                || property.value.to_lowercase().contains(&search)
+                        })
+                        .collect();
+                    if properties.is_empty() {
+                        continue;
+                    }
+                    egui::CollapsingHeader::new(&interface.interface)
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            egui::Grid::new(&interface.interface)
+                                .striped(true)
+                                .num_columns(3)
+                                .show(ui, |ui| {
+                                    for property in properties {
+                                        ui.monospace(&property.name);
+                                        ui.weak(&property.signature);
+                                        ui.label(&property.value);
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
+            });
+    }
+}
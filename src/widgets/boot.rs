@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::{Color32, Ui};
+use egui_extras::Column;
+use poll_promise::Promise;
+
+use crate::format;
+use crate::systemd::{self, BootTimes, UnitTimes};
+
+use super::boot_timeline;
+
+struct BootData {
+    times: BootTimes,
+    units: Vec<UnitTimes>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BootView {
+    Blame,
+    CriticalChain,
+    Timeline,
+}
+
+/// Where the boot time went, like `systemd-analyze time`, `blame` and
+/// `critical-chain`.
+pub struct BootAnalysis {
+    con: zbus::Connection,
+    data: Option<Promise<zbus::Result<BootData>>>,
+    view: BootView,
+    /// The unit the critical chain is shown for, the target that was reached
+    /// last if none was picked.
+    target: Option<String>,
+    error: Option<String>,
+}
+
+impl BootAnalysis {
+    pub fn new(con: zbus::Connection) -> Self {
+        Self {
+            con,
+            data: None,
+            view: BootView::Blame,
+            target: None,
+            error: None,
+        }
+    }
+
+    fn load(&mut self) {
+        let con = self.con.clone();
+        self.data = Some(Promise::spawn_async(async move {
+            Ok(BootData {
+                times: systemd::boot_times(con.clone()).await?,
+                units: systemd::unit_times(con).await?,
+            })
+        }));
+    }
+
+    pub fn draw(&mut self, ui: &mut Ui) {
+        if self.data.is_none() {
+            self.load();
+        }
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.view, BootView::Blame, "Blame");
+            ui.radio_value(&mut self.view, BootView::CriticalChain, "Critical chain");
+            ui.radio_value(&mut self.view, BootView::Timeline, "Timeline");
+            if ui.button("Refresh").clicked() {
+                self.load();
+            }
+            if self.view == BootView::Timeline && ui.button("Export SVG…").clicked() {
+                self.export();
+            }
+        });
+        if let Some(err) = &self.error {
+            ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+        }
+
+        let Some(data) = self.data.as_ref().and_then(Promise::ready) else {
+            ui.spinner();
+            return;
+        };
+        let data = match data {
+            Ok(data) => data,
+            Err(err) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                return;
+            }
+        };
+
+        ui.label(summary(&data.times));
+        ui.separator();
+        match self.view {
+            BootView::Blame => blame(ui, &data.units),
+            BootView::CriticalChain => {
+                let target = self.target.clone().or_else(|| last_target(&data.units));
+                let mut picked = target.clone();
+                egui::ComboBox::from_label("target")
+                    .selected_text(picked.clone().unwrap_or_default())
+                    .show_ui(ui, |ui| {
+                        for unit in data.units.iter().filter(|u| u.name.ends_with(".target")) {
+                            ui.selectable_value(&mut picked, Some(unit.name.clone()), &unit.name);
+                        }
+                    });
+                if picked != target {
+                    self.target = picked;
+                }
+                if let Some(target) = target {
+                    critical_chain(ui, data, &target);
+                }
+            }
+            BootView::Timeline => boot_timeline::timeline(ui, &data.times, &data.units),
+        }
+    }
+
+    fn export(&mut self) {
+        let Some(Ok(data)) = self.data.as_ref().and_then(Promise::ready) else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("boot.svg")
+            .add_filter("SVG", &["svg"])
+            .save_file()
+        else {
+            return;
+        };
+        self.error = std::fs::write(path, boot_timeline::svg(&data.times, &data.units))
+            .err()
+            .map(|err| err.to_string());
+    }
+}
+
+fn summary(times: &BootTimes) -> String {
+    if !times.finished {
+        return "Bootup is not yet finished.".to_owned();
+    }
+    let phases = [
+        (times.firmware, "firmware"),
+        (times.loader, "loader"),
+        (times.kernel, "kernel"),
+        (times.initrd, "initrd"),
+        (times.userspace, "userspace"),
+    ];
+    let parts: Vec<String> = phases
+        .iter()
+        .filter(|(time, _)| *time > 0)
+        .map(|(time, phase)| format!("{} ({phase})", format::duration(*time)))
+        .collect();
+    format!(
+        "Startup finished in {} = {}",
+        parts.join(" + "),
+        format::duration(times.total())
+    )
+}
+
+fn blame(ui: &mut Ui, units: &[UnitTimes]) {
+    let mut blamed: Vec<(u64, &str)> = units
+        .iter()
+        .filter_map(|unit| Some((unit.activation_time()?, unit.name.as_str())))
+        .filter(|(time, _)| *time > 0)
+        .collect();
+    blamed.sort_unstable_by(|a, b| b.cmp(a));
+
+    let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
+    egui_extras::TableBuilder::new(ui)
+        .striped(true)
+        .column(Column::auto().at_least(96.0))
+        .column(Column::remainder())
+        .header(text_height * 1.5, |mut header| {
+            header.col(|ui| {
+                ui.label("time");
+            });
+            header.col(|ui| {
+                ui.label("unit");
+            });
+        })
+        .body(|body| {
+            body.rows(text_height * 1.5, blamed.len(), |index, mut row| {
+                let (time, unit) = blamed[index];
+                row.col(|ui| {
+                    ui.monospace(format::duration(time));
+                });
+                row.col(|ui| {
+                    ui.label(unit);
+                });
+            });
+        });
+}
+
+/// The target that became active last, usually the default one.
+fn last_target(units: &[UnitTimes]) -> Option<String> {
+    units
+        .iter()
+        .filter(|unit| unit.name.ends_with(".target"))
+        .max_by_key(|unit| unit.activated)
+        .map(|unit| unit.name.clone())
+}
+
+fn critical_chain(ui: &mut Ui, data: &BootData, target: &str) {
+    let units: HashMap<&str, &UnitTimes> = data
+        .units
+        .iter()
+        .map(|unit| (unit.name.as_str(), unit))
+        .collect();
+    let Some(target) = units.get(target) else {
+        return;
+    };
+    let finish = if data.times.finished {
+        data.times.userspace_start + data.times.userspace
+    } else {
+        u64::MAX
+    };
+
+    let mut chain = Vec::new();
+    let mut visited = HashSet::new();
+    walk_chain(&units, target, finish, 0, &mut visited, &mut chain);
+
+    ui.label("The time when a unit became active or started is printed after the \"@\" character.");
+    ui.label("The time the unit took to start is printed after the \"+\" character.");
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for (depth, unit) in chain {
+                ui.horizontal(|ui| {
+                    ui.add_space(depth as f32 * 16.0);
+                    ui.label(if depth > 0 { "└─" } else { "" });
+                    ui.label(&unit.name);
+                    let since = |time: u64| {
+                        format::duration(time.saturating_sub(data.times.userspace_start))
+                    };
+                    match unit.activation_time().filter(|time| *time > 0) {
+                        Some(time) => {
+                            ui.weak(format!("@{}", since(unit.activating)));
+                            ui.colored_label(Color32::RED, format!("+{}", format::duration(time)));
+                        }
+                        None => {
+                            ui.weak(format!("@{}", since(unit.activated)));
+                        }
+                    }
+                });
+            }
+        });
+}
+
+/// Follows the `After=` dependencies that became active last, the way
+/// `systemd-analyze critical-chain` does.
+fn walk_chain<'a>(
+    units: &HashMap<&str, &'a UnitTimes>,
+    unit: &'a UnitTimes,
+    finish: u64,
+    depth: usize,
+    visited: &mut HashSet<&'a str>,
+    chain: &mut Vec<(usize, &'a UnitTimes)>,
+) {
+    chain.push((depth, unit));
+    visited.insert(&unit.name);
+
+    let after: Vec<&UnitTimes> = unit
+        .after
+        .iter()
+        .filter_map(|name| units.get(name.as_str()).copied())
+        .filter(|dependency| dependency.activated > 0 && dependency.activated <= finish)
+        .collect();
+    let Some(longest) = after.iter().map(|dependency| dependency.activated).max() else {
+        return;
+    };
+    for dependency in after {
+        if dependency.activated == longest && !visited.contains(dependency.name.as_str()) {
+            walk_chain(units, dependency, finish, depth + 1, visited, chain);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt::Write;
+
+use egui::plot::{Bar, BarChart, Plot};
+use egui::{Color32, Ui};
+
+use crate::format;
+use crate::systemd::{ActiveState, BootTimes, UnitTimes};
+
+use super::unitdata::active_state_to_color;
+
+const USEC_PER_SEC: f64 = 1_000_000.0;
+/// Height of a row in the exported SVG, in pixels.
+const SVG_ROW: f64 = 20.0;
+const SVG_PX_PER_SEC: f64 = 100.0;
+const SVG_LABEL_WIDTH: f64 = 320.0;
+
+/// A span of time a unit or boot phase spent in one state, in seconds since
+/// the kernel started.
+struct Segment {
+    start: f64,
+    end: f64,
+    state: &'static str,
+    color: Color32,
+}
+
+struct Row {
+    label: String,
+    segments: Vec<Segment>,
+}
+
+/// The rows of the timeline: the boot phases first, then the units that
+/// started during the boot in the order they did, like `systemd-analyze plot`.
+fn rows(times: &BootTimes, units: &[UnitTimes]) -> Vec<Row> {
+    let secs = |usec: u64| usec as f64 / USEC_PER_SEC;
+    let finish = if times.finished {
+        times.userspace_start + times.userspace
+    } else {
+        units
+            .iter()
+            .map(|unit| unit.activated.max(unit.deactivated))
+            .max()
+            .unwrap_or(times.userspace_start)
+    };
+
+    let mut rows = Vec::new();
+    let kernel_done = times.kernel;
+    let mut phases = vec![("kernel", 0, kernel_done, Color32::LIGHT_BLUE)];
+    if times.initrd > 0 {
+        phases.push(("initrd", kernel_done, times.userspace_start, Color32::KHAKI));
+    }
+    phases.push((
+        "userspace",
+        times.userspace_start,
+        finish,
+        Color32::LIGHT_GREEN,
+    ));
+    for (phase, start, end, color) in phases {
+        rows.push(Row {
+            label: phase.to_owned(),
+            segments: vec![Segment {
+                start: secs(start),
+                end: secs(end),
+                state: phase,
+                color,
+            }],
+        });
+    }
+
+    let mut started: Vec<&UnitTimes> = units
+        .iter()
+        .filter(|unit| unit.activating > 0 && unit.activating <= finish)
+        .collect();
+    started.sort_by_key(|unit| unit.activating);
+    for unit in started {
+        let mut segments = Vec::new();
+        let mut span = |from: u64, to: u64, state: ActiveState| {
+            // What hasn't ended (yet) runs to the end of the boot.
+            let to = if to < from { finish.max(from) } else { to };
+            segments.push(Segment {
+                start: secs(from),
+                end: secs(to),
+                state: match state {
+                    ActiveState::Activating => "activating",
+                    ActiveState::Active => "active",
+                    _ => "deactivating",
+                },
+                color: active_state_to_color(&state),
+            });
+        };
+        let activated = if unit.activated > 0 {
+            unit.activated
+        } else {
+            unit.deactivating.max(unit.deactivated)
+        };
+        span(unit.activating, activated, ActiveState::Activating);
+        if unit.activated > 0 {
+            let deactivating = if unit.deactivating > unit.activated {
+                unit.deactivating
+            } else {
+                0
+            };
+            span(unit.activated, deactivating, ActiveState::Active);
+            if deactivating > 0 {
+                span(deactivating, unit.deactivated, ActiveState::Deactivating);
+            }
+        }
+        rows.push(Row {
+            label: format!("{} ({})", unit.name, unit.active_state),
+            segments,
+        });
+    }
+    rows
+}
+
+/// Draws the timeline in a plot that can be zoomed, panned and hovered.
+pub fn timeline(ui: &mut Ui, times: &BootTimes, units: &[UnitTimes]) {
+    let rows = rows(times, units);
+    let labels: Vec<String> = rows.iter().map(|row| row.label.clone()).collect();
+
+    let mut bars = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        for segment in &row.segments {
+            // The first row goes on top.
+            bars.push(
+                Bar::new(-(index as f64), segment.end - segment.start)
+                    .base_offset(segment.start)
+                    .width(0.8)
+                    .fill(segment.color)
+                    .name(format!(
+                        "{}\n{} {:.3}s → {:.3}s ({:.3}s)",
+                        row.label,
+                        segment.state,
+                        segment.start,
+                        segment.end,
+                        segment.end - segment.start
+                    )),
+            );
+        }
+    }
+
+    Plot::new("boot_timeline")
+        .allow_boxed_zoom(true)
+        .include_y(0.5)
+        .include_y(-(rows.len() as f64))
+        .x_axis_formatter(|x, _| format!("{x}s"))
+        .y_axis_formatter(move |y, _| {
+            let index = -y;
+            if index.fract() == 0.0 && index >= 0.0 {
+                labels.get(index as usize).cloned().unwrap_or_default()
+            } else {
+                String::new()
+            }
+        })
+        .label_formatter(|name, _| name.to_owned())
+        .show(ui, |plot| {
+            plot.bar_chart(
+                BarChart::new(bars)
+                    .horizontal()
+                    .element_formatter(Box::new(|bar, _| bar.name.clone())),
+            );
+        });
+}
+
+fn svg_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Renders the timeline as a standalone SVG document.
+pub fn svg(times: &BootTimes, units: &[UnitTimes]) -> String {
+    let rows = rows(times, units);
+    let end = rows
+        .iter()
+        .flat_map(|row| row.segments.iter().map(|segment| segment.end))
+        .fold(0.0, f64::max)
+        .ceil();
+    let width = SVG_LABEL_WIDTH + end * SVG_PX_PER_SEC + SVG_ROW;
+    let height = (rows.len() + 2) as f64 * SVG_ROW;
+    let x = |secs: f64| SVG_LABEL_WIDTH + secs * SVG_PX_PER_SEC;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" font-family="sans-serif" font-size="12">"#
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+    for second in 0..=end as u64 {
+        let at = x(second as f64);
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{at}" y1="0" x2="{at}" y2="{height}" stroke="gainsboro"/><text x="{at}" y="{}">{second}s</text>"#,
+            SVG_ROW * 0.75
+        );
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let top = (index + 1) as f64 * SVG_ROW;
+        for segment in &row.segments {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"><title>{} {:.3}s</title></rect>"#,
+                x(segment.start),
+                top + 2.0,
+                (segment.end - segment.start) * SVG_PX_PER_SEC,
+                SVG_ROW - 4.0,
+                format::hex_color(segment.color),
+                segment.state,
+                segment.end - segment.start
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<text x="4" y="{}">{}</text>"#,
+            top + SVG_ROW * 0.75,
+            svg_escape(&row.label)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,111 @@
+use egui::{Color32, Context};
+use poll_promise::Promise;
+use zvariant::OwnedObjectPath;
+
+use crate::systemd::{self, UnitData, CLEAN_MASKS};
+
+/// Asks which of a unit's directories to wipe with `Unit.Clean()`, and for a
+/// confirmation before doing so.
+#[derive(Clone)]
+pub struct CleanDialog {
+    unit: String,
+    path: OwnedObjectPath,
+    /// Masks the unit can be cleaned with, as listed by `CanClean`.
+    cleanable: Vec<String>,
+    directories: Vec<Vec<String>>,
+    selected: [bool; CLEAN_MASKS.len()],
+    confirmed: bool,
+    error: Option<String>,
+}
+
+impl CleanDialog {
+    pub fn new(con: &zbus::Connection, unit: &UnitData, cleanable: Vec<String>) -> Self {
+        let directories = systemd::unit_type_interface(&unit.name)
+            .and_then(|interface| {
+                Promise::spawn_async(systemd::clean_directories(
+                    con.clone(),
+                    unit.object_path.clone(),
+                    interface,
+                ))
+                .block_and_take()
+                .ok()
+            })
+            .unwrap_or_else(|| vec![Vec::new(); CLEAN_MASKS.len()]);
+
+        Self {
+            unit: unit.name.clone(),
+            path: unit.object_path.clone(),
+            cleanable,
+            directories,
+            selected: [false; CLEAN_MASKS.len()],
+            confirmed: false,
+            error: None,
+        }
+    }
+
+    /// Returns false once the dialog is done with, either cleaned or closed.
+    pub fn draw(&mut self, ctx: &Context, con: &zbus::Connection) -> bool {
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new(format!("Clean {}", self.unit))
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("Select what to remove. This can't be undone.");
+                for (index, (mask, directive)) in CLEAN_MASKS.iter().enumerate() {
+                    let available = self.cleanable.iter().any(|c| c == mask);
+                    ui.add_enabled(
+                        available,
+                        egui::Checkbox::new(&mut self.selected[index], *mask),
+                    );
+                    ui.indent(mask, |ui| {
+                        let directories = &self.directories[index];
+                        if directories.is_empty() {
+                            ui.weak(format!("no {directive}="));
+                        }
+                        for directory in directories {
+                            ui.label(format!("{directive}={directory}"));
+                        }
+                    });
+                }
+
+                ui.separator();
+                let mask: Vec<String> = CLEAN_MASKS
+                    .iter()
+                    .zip(self.selected)
+                    .filter(|(_, selected)| *selected)
+                    .map(|((mask, _), _)| mask.to_string())
+                    .collect();
+                ui.checkbox(
+                    &mut self.confirmed,
+                    format!("Yes, permanently delete the {}", mask.join(", ")),
+                );
+                let clean = egui::Button::new("Clean").fill(Color32::DARK_RED);
+                if ui
+                    .add_enabled(self.confirmed && !mask.is_empty(), clean)
+                    .clicked()
+                {
+                    match self.clean(con, mask) {
+                        Ok(()) => done = true,
+                        Err(err) => self.error = Some(err.to_string()),
+                    }
+                }
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                }
+            });
+        open && !done
+    }
+
+    fn clean(&self, con: &zbus::Connection, mask: Vec<String>) -> zbus::Result<()> {
+        let con = con.clone();
+        let path = self.path.clone();
+        Promise::spawn_async(async move {
+            systemd::unit_interface_proxy(&con, path, systemd::UNIT_INTERFACE)
+                .await?
+                .call("Clean", &(mask,))
+                .await
+        })
+        .block_and_take()
+    }
+}
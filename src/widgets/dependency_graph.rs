@@ -0,0 +1,343 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use egui::{
+    emath::Rot2, vec2, Align2, Color32, Context, FontId, Painter, Pos2, Rect, Sense, Stroke, Ui,
+    Vec2,
+};
+use poll_promise::Promise;
+
+use crate::format;
+use crate::systemd::{self, UnitDependencies, DEPENDENCY_PROPERTIES};
+
+use super::unitdata::active_state_to_color;
+
+const NODE_SIZE: Vec2 = vec2(200.0, 24.0);
+const NODE_SPACING: Vec2 = vec2(96.0, 12.0);
+/// Stops a widely wanted target from pulling in the whole system.
+const MAX_NODES: usize = 256;
+
+/// Colors a relation the same way in the graph and in the exported DOT,
+/// by the index of its property in [`DEPENDENCY_PROPERTIES`].
+fn relation_color(property: usize) -> Color32 {
+    // A relation and its reverse share a color.
+    match property / 2 {
+        0 => Color32::LIGHT_GRAY,
+        1 => Color32::GRAY,
+        2 => Color32::LIGHT_BLUE,
+        3 => Color32::from_rgb(0xc0, 0x80, 0xff),
+        4 => Color32::GREEN,
+        _ => Color32::GOLD,
+    }
+}
+
+struct Layout {
+    /// Units by their distance from the root.
+    levels: Vec<Vec<String>>,
+    /// `(from, to, property)` edges between shown units.
+    edges: Vec<(String, String, usize)>,
+    /// Units whose dependencies are shown.
+    followed: HashSet<String>,
+    truncated: bool,
+    /// Some units are still being loaded, so there's more to show.
+    loading: bool,
+}
+
+/// An interactive graph of the dependencies of a unit, which can be exported
+/// as Graphviz DOT.
+pub struct DependencyGraph {
+    root: String,
+    /// How many levels are followed from the root without clicking.
+    depth: usize,
+    relations: [bool; DEPENDENCY_PROPERTIES.len()],
+    /// Units that were clicked to follow their dependencies as well.
+    expanded: HashSet<String>,
+    units: HashMap<String, Promise<Result<UnitDependencies, String>>>,
+    error: Option<String>,
+}
+
+impl DependencyGraph {
+    pub fn new(root: String) -> Self {
+        let mut relations = [false; DEPENDENCY_PROPERTIES.len()];
+        for relation in ["Requires", "Wants", "BindsTo", "PartOf", "Triggers"] {
+            if let Some(index) = DEPENDENCY_PROPERTIES.iter().position(|p| *p == relation) {
+                relations[index] = true;
+            }
+        }
+        Self {
+            root,
+            depth: 1,
+            relations,
+            expanded: HashSet::new(),
+            units: HashMap::new(),
+            error: None,
+        }
+    }
+
+    /// Returns false once the window is closed.
+    pub fn draw(&mut self, ctx: &Context, con: &zbus::Connection) -> bool {
+        let mut open = true;
+        egui::Window::new(format!("Dependencies of {}", self.root))
+            .resizable(true)
+            .default_size([800.0, 480.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let layout = self.layout(con);
+                self.controls(ui, &layout);
+                if layout.loading {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Loading units…");
+                    });
+                    ui.ctx().request_repaint();
+                }
+                if layout.truncated {
+                    ui.colored_label(
+                        Color32::GOLD,
+                        format!("Only the first {MAX_NODES} units are shown."),
+                    );
+                }
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                }
+                ui.separator();
+                egui::ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| self.graph(ui, &layout));
+            });
+        open
+    }
+
+    fn controls(&mut self, ui: &mut Ui, layout: &Layout) {
+        ui.horizontal_wrapped(|ui| {
+            for (index, property) in DEPENDENCY_PROPERTIES.iter().enumerate() {
+                let text = egui::RichText::new(*property).color(relation_color(index));
+                ui.checkbox(&mut self.relations[index], text);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(&mut self.depth, 1..=5).text("depth"));
+            if ui.button("Refresh").clicked() {
+                self.units.clear();
+            }
+            if ui.button("Copy DOT").clicked() {
+                let dot = self.dot(layout);
+                ui.output_mut(|output| output.copied_text = dot);
+            }
+            if ui.button("Export DOT…").clicked() {
+                self.export(layout);
+            }
+        });
+    }
+
+    /// Walks the graph breadth first, loading units as they're reached.
+    fn layout(&mut self, con: &zbus::Connection) -> Layout {
+        let mut layout = Layout {
+            levels: Vec::new(),
+            edges: Vec::new(),
+            followed: HashSet::new(),
+            truncated: false,
+            loading: false,
+        };
+        let mut seen = HashSet::from([self.root.clone()]);
+        let mut queue = VecDeque::from([(self.root.clone(), 0)]);
+        while let Some((unit, level)) = queue.pop_front() {
+            if layout.levels.len() <= level {
+                layout.levels.push(Vec::new());
+            }
+            layout.levels[level].push(unit.clone());
+
+            // Even the units that aren't followed are loaded, for their state.
+            let dependencies = match self.load(con, &unit) {
+                Some(Ok(dependencies)) => dependencies,
+                Some(Err(_)) => continue,
+                None => {
+                    layout.loading = true;
+                    continue;
+                }
+            };
+            let dependencies = dependencies.dependencies.clone();
+            if level >= self.depth && !self.expanded.contains(&unit) {
+                continue;
+            }
+            layout.followed.insert(unit.clone());
+            for (property, targets) in dependencies.iter().enumerate() {
+                if !self.relations[property] {
+                    continue;
+                }
+                for target in targets {
+                    if !seen.contains(target) {
+                        if seen.len() >= MAX_NODES {
+                            layout.truncated = true;
+                            continue;
+                        }
+                        seen.insert(target.clone());
+                        queue.push_back((target.clone(), level + 1));
+                    }
+                    layout.edges.push((unit.clone(), target.clone(), property));
+                }
+            }
+        }
+        layout
+    }
+
+    /// Starts loading `unit` the first time it's reached, `None` until it's
+    /// done.
+    fn load(
+        &mut self,
+        con: &zbus::Connection,
+        unit: &str,
+    ) -> Option<&Result<UnitDependencies, String>> {
+        self.units
+            .entry(unit.to_owned())
+            .or_insert_with(|| {
+                let con = con.clone();
+                let unit = unit.to_owned();
+                Promise::spawn_async(async move {
+                    systemd::unit_dependencies(con, unit)
+                        .await
+                        .map_err(|err| err.to_string())
+                })
+            })
+            .ready()
+    }
+
+    fn graph(&mut self, ui: &mut Ui, layout: &Layout) {
+        let rows = layout.levels.iter().map(Vec::len).max().unwrap_or(0);
+        let cell = NODE_SIZE + NODE_SPACING;
+        let size = vec2(layout.levels.len() as f32 * cell.x, rows as f32 * cell.y);
+        let (response, painter) = ui.allocate_painter(size, Sense::hover());
+        let origin = response.rect.min;
+
+        let mut rects = HashMap::new();
+        for (level, units) in layout.levels.iter().enumerate() {
+            for (row, unit) in units.iter().enumerate() {
+                let min = origin + vec2(level as f32 * cell.x, row as f32 * cell.y);
+                rects.insert(unit.as_str(), Rect::from_min_size(min, NODE_SIZE));
+            }
+        }
+
+        for (from, to, property) in &layout.edges {
+            if let (Some(from), Some(to)) = (rects.get(from.as_str()), rects.get(to.as_str())) {
+                let stroke = Stroke::new(1.0, relation_color(*property));
+                arrow(&painter, from.right_center(), to.left_center(), stroke);
+            }
+        }
+
+        let mut clicked = None;
+        let mut new_root = None;
+        for (unit, rect) in &rects {
+            let state = match self.units.get(*unit).and_then(Promise::ready) {
+                Some(Ok(dependencies)) => Some(&dependencies.active_state),
+                _ => None,
+            };
+            let color = state.map_or(Color32::DEBUG_COLOR, active_state_to_color);
+            let state_text = state.map_or_else(String::new, |state| state.to_string());
+            painter.rect(
+                *rect,
+                4.0,
+                ui.visuals().extreme_bg_color,
+                Stroke::new(2.0, color),
+            );
+            let followed = layout.followed.contains(*unit);
+            let text = if followed {
+                unit.to_string()
+            } else {
+                format!("{unit} …")
+            };
+            painter.with_clip_rect(rect.shrink(2.0)).text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                text,
+                FontId::proportional(13.0),
+                ui.visuals().text_color(),
+            );
+
+            let response = ui
+                .interact(*rect, ui.id().with(unit), Sense::click())
+                .on_hover_text(match self.units.get(*unit).and_then(Promise::ready) {
+                    Some(Err(err)) => err.clone(),
+                    _ if self.expanded.contains(*unit) => {
+                        format!("{state_text}, click to collapse")
+                    }
+                    _ if !followed => format!("{state_text}, click to expand"),
+                    _ => state_text.clone(),
+                });
+            if response.clicked() && (followed == self.expanded.contains(*unit)) {
+                clicked = Some(unit.to_string());
+            }
+            response.context_menu(|ui| {
+                if ui.button("Show dependencies from here").clicked() {
+                    new_root = Some(unit.to_string());
+                    ui.close_menu();
+                }
+            });
+        }
+
+        if let Some(unit) = clicked {
+            if !self.expanded.remove(&unit) {
+                self.expanded.insert(unit);
+            }
+        }
+        if let Some(root) = new_root {
+            self.root = root;
+            self.expanded.clear();
+        }
+    }
+
+    /// Writes the shown subgraph in the format of `systemd-analyze dot`.
+    fn dot(&self, layout: &Layout) -> String {
+        let quote = |unit: &str| format!("\"{}\"", unit.replace('"', "\\\""));
+        let mut dot = format!("digraph {} {{\n\trankdir=LR;\n", quote(&self.root));
+        for unit in layout.levels.iter().flatten() {
+            let color = match self.units.get(unit).and_then(Promise::ready) {
+                Some(Ok(dependencies)) => active_state_to_color(&dependencies.active_state),
+                _ => Color32::GRAY,
+            };
+            let _ = writeln!(
+                dot,
+                "\t{} [color=\"{}\"];",
+                quote(unit),
+                format::hex_color(color)
+            );
+        }
+        for (from, to, property) in &layout.edges {
+            let _ = writeln!(
+                dot,
+                "\t{} -> {} [color=\"{}\", label=\"{}\"];",
+                quote(from),
+                quote(to),
+                format::hex_color(relation_color(*property)),
+                DEPENDENCY_PROPERTIES[*property]
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn export(&mut self, layout: &Layout) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(&format!("{}.dot", self.root))
+            .add_filter("Graphviz", &["dot", "gv"])
+            .save_file()
+        else {
+            return;
+        };
+        self.error = std::fs::write(path, self.dot(layout))
+            .err()
+            .map(|err| err.to_string());
+    }
+}
+
+/// Draws a line with a fixed-size arrowhead at `to`.
+fn arrow(painter: &Painter, from: Pos2, to: Pos2, stroke: Stroke) {
+    let tip_length = 8.0;
+    let direction = (to - from).normalized();
+    let rotation = Rot2::from_angle(std::f32::consts::TAU / 12.0);
+    painter.line_segment([from, to], stroke);
+    painter.line_segment([to, to - tip_length * (rotation * direction)], stroke);
+    painter.line_segment(
+        [to, to - tip_length * (rotation.inverse() * direction)],
+        stroke,
+    );
+}
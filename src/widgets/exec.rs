@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Ui};
+use poll_promise::Promise;
+
+use crate::exit_status;
+use crate::format;
+use crate::systemd::{self, ExecCommand, ExecDetails, UnitData, EXEC_PROPERTIES};
+
+/// Often enough to follow `StatusText` as the service updates it.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// How a service is launched: its commands, credentials and environment.
+#[derive(Default)]
+pub struct ExecPanel {
+    unit: Option<String>,
+    details: Option<zbus::Result<ExecDetails>>,
+    refreshed: Option<Instant>,
+    /// The variables the user clicked to see the value of.
+    revealed: HashSet<String>,
+}
+
+impl ExecPanel {
+    pub fn draw(&mut self, ui: &mut Ui, con: &zbus::Connection, unit: &UnitData) {
+        if self.unit.as_deref() != Some(unit.name.as_str()) {
+            *self = Self {
+                unit: Some(unit.name.clone()),
+                ..Default::default()
+            };
+        }
+        if !matches!(self.refreshed, Some(at) if at.elapsed() < REFRESH_INTERVAL) {
+            self.refreshed = Some(Instant::now());
+            self.details = Some(
+                Promise::spawn_async(systemd::exec_details(con.clone(), unit.object_path.clone()))
+                    .block_and_take(),
+            );
+        }
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+
+        let details = match &self.details {
+            Some(Ok(details)) => details,
+            Some(Err(err)) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                return;
+            }
+            None => return,
+        };
+
+        egui::Grid::new("exec_details")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let or_default = |value: &str, default: &str| {
+                    if value.is_empty() {
+                        default.to_owned()
+                    } else {
+                        value.to_owned()
+                    }
+                };
+                for (label, value) in [
+                    ("Type:", details.service_type.clone()),
+                    ("Restart:", details.restart.clone()),
+                    ("User:", or_default(&details.user, "root")),
+                    ("Group:", or_default(&details.group, "root")),
+                    (
+                        "Working directory:",
+                        or_default(&details.working_directory, "/"),
+                    ),
+                ] {
+                    ui.label(label);
+                    ui.monospace(value);
+                    ui.end_row();
+                }
+                ui.label("Main PID:");
+                match details.main_pid {
+                    0 => ui.weak("none"),
+                    pid => ui.monospace(pid.to_string()),
+                };
+                ui.end_row();
+                if !details.status_text.is_empty() {
+                    ui.label("Status:");
+                    ui.colored_label(Color32::LIGHT_BLUE, &details.status_text);
+                    ui.end_row();
+                }
+            });
+
+        for (property, commands) in EXEC_PROPERTIES.iter().zip(&details.commands) {
+            if commands.is_empty() {
+                continue;
+            }
+            ui.separator();
+            ui.strong(*property);
+            for command in commands {
+                self::command(ui, command);
+            }
+        }
+
+        if !details.environment.is_empty() || !details.environment_files.is_empty() {
+            ui.separator();
+            ui.strong("Environment");
+            egui::Grid::new("exec_environment")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for assignment in &details.environment {
+                        let (name, value) = assignment.split_once('=').unwrap_or((assignment, ""));
+                        ui.monospace(name);
+                        if !format::looks_secret(name) || self.revealed.contains(name) {
+                            ui.monospace(value);
+                        } else if ui
+                            .link(format::SECRET_MASK)
+                            .on_hover_text("Click to reveal")
+                            .clicked()
+                        {
+                            self.revealed.insert(name.to_owned());
+                        }
+                        ui.end_row();
+                    }
+                });
+            for (file, may_be_missing) in &details.environment_files {
+                ui.horizontal(|ui| {
+                    ui.label("From");
+                    ui.monospace(file);
+                    if *may_be_missing {
+                        ui.weak("(if it exists)");
+                    }
+                });
+            }
+        }
+    }
+}
+
+fn command(ui: &mut Ui, command: &ExecCommand) {
+    let prefix = if command.ignore_failure { "-" } else { "" };
+    ui.monospace(format!("{prefix}{}", command.argv.join(" ")))
+        .on_hover_text(&command.path);
+    let last_run = match (command.started, command.exited) {
+        (0, _) => "never ran".to_owned(),
+        (started, 0) => format!(
+            "PID {} running since {}",
+            command.pid,
+            format::timestamp(started)
+        ),
+        (_, exited) => format!(
+            "PID {} {} at {}",
+            command.pid,
+            exit_status::describe(command.code, command.status),
+            format::timestamp(exited)
+        ),
+    };
+    let failed = command.exited > 0 && !(command.code == libc::CLD_EXITED && command.status == 0);
+    if failed && !command.ignore_failure {
+        ui.colored_label(Color32::RED, last_run);
+    } else {
+        ui.weak(last_run);
+    }
+}
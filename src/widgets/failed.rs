@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Ui};
+use egui_extras::Column;
+use poll_promise::Promise;
+
+use crate::systemd::{self, UnitData};
+
+use super::unitdata::active_state_to_color;
+
+/// How often the list is reread, so recovered units drop out of it.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A manager to list failed units of, e.g. the system or the user one.
+pub struct Bus<'a> {
+    pub name: &'static str,
+    pub con: &'a zbus::Connection,
+}
+
+struct FailedUnit {
+    bus: &'static str,
+    unit: UnitData,
+}
+
+/// Every failed unit across the given managers, with bulk actions.
+#[derive(Default)]
+pub struct FailedUnits {
+    units: Vec<FailedUnit>,
+    /// `(bus, unit)` pairs picked for the bulk actions.
+    selected: HashSet<(&'static str, String)>,
+    refreshed: Option<Instant>,
+    errors: Vec<String>,
+}
+
+impl FailedUnits {
+    pub fn draw(&mut self, ui: &mut Ui, buses: &[Bus<'_>]) {
+        if !matches!(self.refreshed, Some(at) if at.elapsed() < REFRESH_INTERVAL) {
+            self.refresh(buses);
+        }
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+
+        ui.horizontal(|ui| {
+            let all = !self.units.is_empty() && self.selected.len() == self.units.len();
+            let mut toggled = all;
+            if ui.checkbox(&mut toggled, "Select all").changed() {
+                self.selected = if toggled {
+                    self.units
+                        .iter()
+                        .map(|failed| (failed.bus, failed.unit.name.clone()))
+                        .collect()
+                } else {
+                    HashSet::new()
+                };
+            }
+
+            let any = !self.selected.is_empty();
+            if ui
+                .add_enabled(any, egui::Button::new("Reset failed"))
+                .clicked()
+            {
+                self.bulk(buses, |con, units| {
+                    Promise::spawn_async(systemd::reset_failed_units(con, units)).block_and_take()
+                });
+            }
+            if ui.add_enabled(any, egui::Button::new("Restart")).clicked() {
+                self.bulk(buses, |con, units| {
+                    Promise::spawn_async(systemd::restart_units(con, units)).block_and_take()
+                });
+            }
+            if ui.button("Refresh").clicked() {
+                self.refresh(buses);
+            }
+        });
+
+        for err in &self.errors {
+            ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+        }
+        if self.units.is_empty() {
+            ui.label("No failed units.");
+            return;
+        }
+        self.table(ui);
+    }
+
+    fn refresh(&mut self, buses: &[Bus<'_>]) {
+        self.refreshed = Some(Instant::now());
+        self.errors.clear();
+        self.units.clear();
+        for bus in buses {
+            match Promise::spawn_async(systemd::list_failed_units(bus.con.clone())).block_and_take()
+            {
+                Ok(units) => self.units.extend(units.into_iter().map(|unit| FailedUnit {
+                    bus: bus.name,
+                    unit,
+                })),
+                Err(err) => self.errors.push(format!("{}: {err}", bus.name)),
+            }
+        }
+        // Forget the units that recovered meanwhile.
+        let units = &self.units;
+        self.selected.retain(|(bus, name)| {
+            units
+                .iter()
+                .any(|failed| failed.bus == *bus && failed.unit.name == *name)
+        });
+    }
+
+    /// Runs `action` once per bus with the selected units on it.
+    fn bulk<F>(&mut self, buses: &[Bus<'_>], mut action: F)
+    where
+        F: FnMut(zbus::Connection, Vec<String>) -> zbus::Result<()>,
+    {
+        let mut errors = Vec::new();
+        for bus in buses {
+            let units: Vec<String> = self
+                .selected
+                .iter()
+                .filter(|(name, _)| *name == bus.name)
+                .map(|(_, unit)| unit.clone())
+                .collect();
+            if units.is_empty() {
+                continue;
+            }
+            if let Err(err) = action(bus.con.clone(), units) {
+                errors.push(format!("{}: {err}", bus.name));
+            }
+        }
+        self.refresh(buses);
+        self.errors.extend(errors);
+    }
+
+    fn table(&mut self, ui: &mut Ui) {
+        let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
+        let units = &self.units;
+        let selected = &mut self.selected;
+        egui_extras::TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .column(Column::auto().at_least(24.0))
+            .column(Column::auto().at_least(48.0))
+            .column(Column::auto().at_least(192.0))
+            .column(Column::auto().at_least(64.0))
+            .column(Column::remainder())
+            .header(text_height * 1.5, |mut header| {
+                for title in ["", "bus", "unit", "active", "description"] {
+                    header.col(|ui| {
+                        ui.label(title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(text_height * 1.5, units.len(), |index, mut row| {
+                    let failed = &units[index];
+                    let key = (failed.bus, failed.unit.name.clone());
+                    row.col(|ui| {
+                        let mut checked = selected.contains(&key);
+                        if ui.checkbox(&mut checked, "").changed() {
+                            if checked {
+                                selected.insert(key.clone());
+                            } else {
+                                selected.remove(&key);
+                            }
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(failed.bus);
+                    });
+                    row.col(|ui| {
+                        ui.style_mut().wrap = Some(false);
+                        ui.label(&failed.unit.name);
+                    });
+                    row.col(|ui| {
+                        ui.colored_label(
+                            active_state_to_color(&failed.unit.active_status),
+                            failed.unit.active_status.to_string(),
+                        );
+                    });
+                    row.col(|ui| {
+                        ui.style_mut().wrap = Some(false);
+                        ui.label(&failed.unit.description);
+                    });
+                });
+            });
+    }
+}
@@ -0,0 +1,165 @@
+use std::time::{Duration, Instant};
+
+use ::systemd::journal::OpenOptions;
+use egui::{Color32, Ui};
+use poll_promise::Promise;
+
+use crate::error::Error;
+use crate::exit_status;
+use crate::format;
+use crate::journal::{self, Entry};
+use crate::systemd::{self, ActiveState, FailureInfo, UnitData};
+
+/// How many journal lines of the failed invocation to show.
+const JOURNAL_LINES: usize = 50;
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+type EntriesPromise = Promise<Result<Vec<Entry>, Error>>;
+
+/// Explains why a unit is in the failed state.
+#[derive(Default)]
+pub struct FailureAnalysis {
+    unit: Option<String>,
+    info: Option<zbus::Result<FailureInfo>>,
+    refreshed: Option<Instant>,
+    /// The invocation the entries are for, and the entries.
+    journal: Option<(String, EntriesPromise)>,
+}
+
+impl FailureAnalysis {
+    pub fn draw(
+        &mut self,
+        ui: &mut Ui,
+        con: &zbus::Connection,
+        options: &OpenOptions,
+        unit: &UnitData,
+    ) {
+        if self.unit.as_deref() != Some(unit.name.as_str()) {
+            *self = Self {
+                unit: Some(unit.name.clone()),
+                ..Default::default()
+            };
+        }
+        if !matches!(self.refreshed, Some(at) if at.elapsed() < REFRESH_INTERVAL) {
+            self.refresh(con, unit);
+        }
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+
+        let info = match &self.info {
+            Some(Ok(info)) if matches!(info.active_state, ActiveState::Failed) => info.clone(),
+            Some(Err(err)) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                return;
+            }
+            _ => return,
+        };
+
+        egui::CollapsingHeader::new("Why did it fail?")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("failure_analysis")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        if let Some(result) = &info.result {
+                            ui.label("Result:");
+                            ui.colored_label(Color32::RED, result);
+                            ui.end_row();
+                        }
+                        if let Some((code, status)) = info.exec_main {
+                            ui.label("Main process:");
+                            ui.label(exit_status::describe(code, status));
+                            ui.end_row();
+                        }
+                        if let Some(restarts) = info.restarts {
+                            ui.label("Restarts:");
+                            ui.label(restarts.to_string());
+                            ui.end_row();
+                        }
+                        ui.label("Start limit:");
+                        let limit = format!(
+                            "{} starts in {}",
+                            info.start_limit_burst,
+                            format::timespan(info.start_limit_interval_usec)
+                        );
+                        if info.hit_start_limit() {
+                            ui.colored_label(Color32::RED, format!("hit, {limit}"));
+                        } else {
+                            ui.label(limit);
+                        }
+                        ui.end_row();
+                    });
+
+                for (title, failed) in [
+                    ("Failed conditions", &info.failed_conditions),
+                    ("Failed asserts", &info.failed_asserts),
+                ] {
+                    if !failed.is_empty() {
+                        ui.label(title);
+                        for condition in failed {
+                            ui.colored_label(Color32::GOLD, condition.to_string());
+                        }
+                    }
+                }
+
+                self.journal(ui, options, &info.invocation);
+            });
+    }
+
+    fn refresh(&mut self, con: &zbus::Connection, unit: &UnitData) {
+        self.refreshed = Some(Instant::now());
+        self.info = Some(
+            Promise::spawn_async(systemd::failure_info(
+                con.clone(),
+                unit.object_path.clone(),
+                unit.name.clone(),
+            ))
+            .block_and_take(),
+        );
+    }
+
+    fn journal(&mut self, ui: &mut Ui, options: &OpenOptions, invocation: &str) {
+        ui.separator();
+        if invocation.is_empty() {
+            ui.label("The unit never ran, so there's no journal.");
+            return;
+        }
+        if !matches!(&self.journal, Some((loaded, _)) if loaded == invocation) {
+            let options = options.clone();
+            let id = invocation.to_owned();
+            self.journal = Some((
+                invocation.to_owned(),
+                Promise::spawn_blocking(move || {
+                    journal::invocation_entries(options, &id, JOURNAL_LINES)
+                }),
+            ));
+        }
+
+        ui.label(format!("Journal of invocation {invocation}"));
+        match self
+            .journal
+            .as_ref()
+            .and_then(|(_, entries)| entries.ready())
+        {
+            Some(Ok(entries)) => {
+                egui::ScrollArea::vertical()
+                    .max_height(256.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for entry in entries {
+                            ui.horizontal(|ui| {
+                                ui.monospace(format::short_date(entry.timestamp()));
+                                ui.add(entry.priority());
+                                ui.label(entry.message());
+                            });
+                        }
+                    });
+            }
+            Some(Err(err)) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+            }
+            None => {
+                ui.spinner();
+            }
+        }
+    }
+}
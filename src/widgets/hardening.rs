@@ -0,0 +1,276 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use ::systemd::journal::OpenOptions;
+use egui::{Color32, Context, Ui};
+use poll_promise::Promise;
+use zvariant::OwnedObjectPath;
+
+use crate::error::Error;
+use crate::journal::{self, Entry, Priority};
+use crate::security::Exposure;
+use crate::systemd::{self, ActiveState, UnitData};
+
+use super::security::score_color;
+use super::unitdata::active_state_to_color;
+
+const DROP_IN: &str = "hardening.conf";
+/// How often the unit is looked at again after a restart, as a sandbox that
+/// breaks it may only show after a while.
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const JOURNAL_LINES: usize = 100;
+
+type EntriesPromise = Promise<Result<Vec<Entry>, Error>>;
+
+/// What was done to the unit, so it can be undone.
+struct Applied {
+    /// What the drop-in contained before, None if it didn't exist.
+    previous: Option<String>,
+    checked: Option<Instant>,
+    state: Option<ActiveState>,
+    journal: Option<EntriesPromise>,
+}
+
+/// Walks through hardening a unit one directive at a time, then writes the
+/// picked ones to a `hardening.conf` drop-in and restarts the unit.
+pub struct HardeningAssistant {
+    unit: String,
+    path: OwnedObjectPath,
+    exposure: Exposure,
+    /// Which checks to harden, by their index in the exposure.
+    selected: Vec<bool>,
+    drop_in_dir: Result<PathBuf, String>,
+    applied: Option<Applied>,
+    error: Option<String>,
+}
+
+impl HardeningAssistant {
+    pub fn new(con: &zbus::Connection, unit: &UnitData, exposure: Exposure) -> Self {
+        let drop_in_dir = Promise::spawn_async(systemd::drop_in_dir(
+            con.clone(),
+            unit.object_path.clone(),
+            unit.name.clone(),
+        ))
+        .block_and_take()
+        .map_err(|err| err.to_string());
+        Self {
+            unit: unit.name.clone(),
+            path: unit.object_path.clone(),
+            selected: vec![false; exposure.checks.len()],
+            exposure,
+            drop_in_dir,
+            applied: None,
+            error: None,
+        }
+    }
+
+    fn drop_in(&self) -> String {
+        let mut drop_in = format!(
+            "# Written by services-gui to sandbox {}.\n[Service]\n",
+            self.unit
+        );
+        for (check, _) in self
+            .exposure
+            .checks
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, selected)| **selected)
+        {
+            drop_in.push_str(&check.hardening);
+            drop_in.push('\n');
+        }
+        drop_in
+    }
+
+    /// Returns false once the window is closed.
+    pub fn draw(&mut self, ctx: &Context, con: &zbus::Connection, options: &OpenOptions) -> bool {
+        let mut open = true;
+        egui::Window::new(format!("Harden {}", self.unit))
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                self.directives(ui);
+                ui.separator();
+
+                ui.label("Drop-in:");
+                let mut drop_in = self.drop_in();
+                ui.add(
+                    egui::TextEdit::multiline(&mut drop_in)
+                        .code_editor()
+                        .interactive(false),
+                );
+                let path = match &self.drop_in_dir {
+                    Ok(dir) => dir.join(DROP_IN),
+                    Err(err) => {
+                        ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                        return;
+                    }
+                };
+                ui.weak(path.display().to_string());
+
+                ui.horizontal(|ui| {
+                    let any = self.selected.iter().any(|selected| *selected);
+                    if ui
+                        .add_enabled(any, egui::Button::new("Write, reload and restart"))
+                        .clicked()
+                    {
+                        self.error = self.apply(con, &path, drop_in).err();
+                    }
+                    if self.applied.is_some() && ui.button("Roll back").clicked() {
+                        self.error = self.roll_back(con, &path).err();
+                    }
+                });
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                }
+
+                self.aftermath(ui, con, options);
+            });
+        open
+    }
+
+    fn directives(&mut self, ui: &mut Ui) {
+        let current = self.exposure.score();
+        let selected = &self.selected;
+        let predicted = self.exposure.predicted(|index, _| selected[index]);
+        ui.horizontal(|ui| {
+            ui.label("Exposure:");
+            ui.colored_label(score_color(current), format!("{current:.1}"));
+            ui.label("→");
+            ui.colored_label(score_color(predicted), format!("{predicted:.1}"));
+        });
+
+        ui.label("Pick the directives to add. Each one can break a service that needs what it takes away.");
+        egui::ScrollArea::vertical()
+            .max_height(256.0)
+            .show(ui, |ui| {
+                for (index, check) in self.exposure.checks.iter().enumerate() {
+                    // Only what would lower the score is worth offering.
+                    if !matches!(check.badness, Some(badness) if badness > 0) {
+                        continue;
+                    }
+                    let gain = current - self.exposure.predicted(|i, _| i == index);
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.selected[index], "");
+                        ui.monospace(&check.hardening);
+                        ui.weak(format!("−{gain:.2}"));
+                    })
+                    .response
+                    .on_hover_text(check.description);
+                }
+            });
+    }
+
+    fn apply(
+        &mut self,
+        con: &zbus::Connection,
+        path: &Path,
+        drop_in: String,
+    ) -> Result<(), String> {
+        // Keep what was there before the first write, to roll back to it.
+        let previous = match &self.applied {
+            Some(applied) => applied.previous.clone(),
+            None => std::fs::read_to_string(path).ok(),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+        std::fs::write(path, drop_in).map_err(|err| err.to_string())?;
+        self.applied = Some(Applied {
+            previous,
+            checked: None,
+            state: None,
+            journal: None,
+        });
+        self.restart(con)
+    }
+
+    fn roll_back(&mut self, con: &zbus::Connection, path: &Path) -> Result<(), String> {
+        let Some(applied) = self.applied.take() else {
+            return Ok(());
+        };
+        match &applied.previous {
+            Some(previous) => std::fs::write(path, previous),
+            None => std::fs::remove_file(path),
+        }
+        .map_err(|err| err.to_string())?;
+        self.restart(con)
+    }
+
+    fn restart(&self, con: &zbus::Connection) -> Result<(), String> {
+        Promise::spawn_async(systemd::reload_and_restart(con.clone(), self.unit.clone()))
+            .block_and_take()
+            .map_err(|err| err.to_string())
+    }
+
+    /// Shows how the unit is doing since the drop-in was written, with the
+    /// warnings and errors it logged.
+    fn aftermath(&mut self, ui: &mut Ui, con: &zbus::Connection, options: &OpenOptions) {
+        let Some(applied) = &mut self.applied else {
+            return;
+        };
+        if !matches!(applied.checked, Some(at) if at.elapsed() < CHECK_INTERVAL) {
+            applied.checked = Some(Instant::now());
+            applied.state =
+                Promise::spawn_async(systemd::unit_active_state(con.clone(), self.path.clone()))
+                    .block_and_take()
+                    .ok();
+            let invocation =
+                Promise::spawn_async(systemd::invocation_id(con.clone(), self.path.clone()))
+                    .block_and_take()
+                    .unwrap_or_default();
+            let reading = matches!(&applied.journal, Some(journal) if journal.ready().is_none());
+            if !invocation.is_empty() && !reading {
+                let options = options.clone();
+                applied.journal = Some(Promise::spawn_blocking(move || {
+                    journal::invocation_entries(options, &invocation, JOURNAL_LINES)
+                }));
+            }
+        }
+        ui.ctx().request_repaint_after(CHECK_INTERVAL);
+
+        ui.separator();
+        if let Some(state) = &applied.state {
+            ui.horizontal(|ui| {
+                ui.label("Now:");
+                ui.colored_label(active_state_to_color(state), state.to_string());
+            });
+        }
+        match applied.journal.as_ref().and_then(Promise::ready) {
+            Some(Ok(entries)) => {
+                let problems: Vec<&Entry> = entries
+                    .iter()
+                    .filter(|entry| {
+                        matches!(
+                            entry.priority(),
+                            Priority::Emergency
+                                | Priority::Alert
+                                | Priority::Critical
+                                | Priority::Error
+                                | Priority::Warning
+                        )
+                    })
+                    .collect();
+                if problems.is_empty() {
+                    ui.label("No warnings or errors logged since the restart.");
+                }
+                egui::ScrollArea::vertical()
+                    .id_source("hardening_journal")
+                    .max_height(192.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for entry in problems {
+                            ui.horizontal(|ui| {
+                                ui.add(entry.priority());
+                                ui.label(entry.message());
+                            });
+                        }
+                    });
+            }
+            Some(Err(err)) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+            }
+            None => (),
+        }
+    }
+}
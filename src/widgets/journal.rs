@@ -1,74 +1,277 @@
-use crate::journal::{JournalReader, Priority};
-use egui::{Color32, Context, Ui, Widget};
+use crate::error::Error;
+use crate::export::{self, ExportFormat};
+use crate::journal::{
+    Entry, JournalFilter, JournalReader, JournalSource, Priority, WINDOW_CAPACITY,
+};
+use egui::{Color32, Context, Sense, Ui, Widget};
 use egui_extras::Column;
+use poll_promise::Promise;
+use std::time::Duration;
 
-use systemd::journal::OpenOptions;
+use super::journal_entry;
+use super::journal_filter::FilterBar;
+use super::journal_search::{self, SearchBar};
+
+/// How many of the last entries are read when the window opens, by default.
+const DEFAULT_BACKLOG: usize = 1000;
+/// How often to look for entries the worker read, while the window is open.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+/// Where the filter is kept across restarts.
+const FILTER_ID: &str = "journal_filter";
 
 pub struct JournalWindow {
-    target: Option<String>,
-    filtered: Vec<usize>,
+    filter: JournalFilter,
+    filter_bar: FilterBar,
+    search: SearchBar,
+    /// Whether the filter saved by the last run was put back.
+    restored: bool,
     open: bool,
-    reader: JournalReader,
+    /// Only while the window is open, so nothing is read in the background
+    /// otherwise.
+    reader: Option<JournalReader>,
+    source: JournalSource,
+    /// How many entries to start with at the tail of the journal.
+    backlog: usize,
+    /// The row to bring back into view after older entries were put in front,
+    /// kept pointing at the same entry as others are trimmed off.
+    scroll_to: Option<usize>,
+    /// The entry shown in the detail pane.
+    selected: Option<Entry>,
+    /// Keep the newest entries in view as they come in.
+    follow: bool,
+    /// The last export, and how many entries it wrote.
+    export: Option<Promise<Result<usize, Error>>>,
 }
 
 impl JournalWindow {
-    pub fn new(options: OpenOptions) -> JournalWindow {
+    pub fn new(source: JournalSource) -> JournalWindow {
         Self {
-            reader: JournalReader::new(options),
-            filtered: Vec::default(),
-            target: None,
+            reader: None,
+            filter: JournalFilter::default(),
+            filter_bar: FilterBar::new(source.clone()),
+            search: SearchBar::new(),
+            // The filter kept is for this machine's journal.
+            restored: !source.is_local(),
             open: false,
+            source,
+            backlog: DEFAULT_BACKLOG,
+            scroll_to: None,
+            selected: None,
+            follow: false,
+            export: None,
         }
     }
 
+    /// Shows the journal of `target`, or of all units, keeping the other
+    /// filters.
     pub fn open(&mut self, target: Option<String>) {
-        self.target = target.clone();
         self.open = true;
-
-        if let Some(target) = target {
-            self.filtered = self.reader.filter(&mut |entry| entry.is_for_unit(&target));
-        } else {
-            self.filtered = self.reader.filter(&mut |_| true);
+        let units: Vec<String> = target.into_iter().collect();
+        if units != self.filter.units {
+            self.filter.units = units;
+            // Read again once shown.
+            self.reader = None;
         }
     }
 
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Starts over with a reader for the current filter.
+    fn reload(&mut self) {
+        let reader = JournalReader::new(self.source.clone(), self.filter.clone(), self.backlog);
+        self.scroll_to = None;
+        self.search.restart(&reader);
+        self.reader = Some(reader);
+    }
+
     pub fn update(&mut self, ctx: &Context) {
-        self.reader.receive();
-
-        if let Some(last) = self.filtered.last().copied() {
-            if let Some(target) = &self.target {
-                self.reader.continue_filter(
-                    &mut |entry| entry.is_for_unit(target),
-                    &mut self.filtered,
-                    last,
-                );
-            } else {
-                self.reader
-                    .continue_filter(&mut |_| true, &mut self.filtered, last);
-                self.filtered = self.reader.filter(&mut |_| true);
+        if !self.open {
+            // Stops the worker.
+            self.reader = None;
+            return;
+        }
+        let id = egui::Id::new(FILTER_ID);
+        if !self.restored {
+            self.restored = true;
+            if let Some(filter) = ctx.data_mut(|data| data.get_persisted::<JournalFilter>(id)) {
+                // The unit asked for by `open` wins, and the boot saved is
+                // most likely not the one running now.
+                let filter = JournalFilter {
+                    units: self.filter.units.clone(),
+                    boot: self.filter.boot.clone(),
+                    ..filter
+                };
+                if filter != self.filter {
+                    self.filter = filter;
+                    self.reader = None;
+                }
+            }
+        }
+        if self.reader.is_none() {
+            self.reload();
+        }
+        // Put back below, unless the window closes; the reader is `None`
+        // meanwhile.
+        let Some(mut reader) = self.reader.take() else {
+            return;
+        };
+        if reader.receive() {
+            // Keep the rows that were in view where they were.
+            let prepended = reader.take_prepended();
+            if prepended > 0 {
+                self.scroll_to = Some(prepended);
+            }
+            let trimmed = reader.take_trimmed();
+            if trimmed > 0 {
+                self.scroll_to = self.scroll_to.map(|row| row.saturating_sub(trimmed));
             }
+            self.search.restart(&reader);
         }
+        ctx.request_repaint_after(REFRESH_INTERVAL);
+        let mut reload = false;
         let mut open = self.open;
-        egui::Window::new("Journal")
+        egui::Window::new(self.source.name())
             .resizable(true)
             .open(&mut open)
             .show(ctx, |ui| {
-                ui.label(format!("Showing journal for {:?}", self.target));
-                ui.label(format!("Having {} entries", self.filtered.len()));
+                let mut filter = self.filter.clone();
+                self.filter_bar.draw(ui, &mut filter);
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("Having {} entries", reader.len()));
+                    if let Some(err) = reader.error() {
+                        ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                    } else if reader.is_loading() {
+                        ui.spinner();
+                    } else if reader.is_empty() {
+                        ui.weak("(the journal is empty)");
+                    }
+                    ui.separator();
+                    ui.label("Backlog:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.backlog).clamp_range(1..=WINDOW_CAPACITY),
+                    );
+                    if ui.button("Reload").clicked() {
+                        reload = true;
+                    }
+                    ui.separator();
+                    if ui.checkbox(&mut self.follow, "Follow").changed() && self.follow {
+                        // Back to the tail, if scrolling paged it out.
+                        if !reader.at_tail() {
+                            reload = true;
+                        }
+                        self.scroll_to = reader.len().checked_sub(1);
+                    }
+                    let paused = reader.is_paused();
+                    if paused {
+                        if ui.button("Resume").clicked() {
+                            reader.set_paused(false);
+                            self.search.restart(&reader);
+                        }
+                        ui.colored_label(
+                            Color32::LIGHT_BLUE,
+                            format!("{} new entries", reader.pending()),
+                        );
+                    } else if ui.button("Pause").clicked() {
+                        reader.set_paused(true);
+                    }
+                    ui.separator();
+                    self.export_menu(ui);
+                });
+                if let Some(row) = self.search.draw(ui, &reader) {
+                    self.scroll_to = Some(row);
+                }
+
+                if let Some(entry) = &self.selected {
+                    let close = egui::SidePanel::right(ui.id().with("journal_entry_pane"))
+                        .resizable(true)
+                        .default_width(320.0)
+                        .show_inside(ui, |ui| journal_entry::draw(ui, entry, &mut filter))
+                        .inner;
+                    if close {
+                        self.selected = None;
+                    }
+                }
 
-                egui::ScrollArea::both()
+                egui::ScrollArea::horizontal()
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                        self.table(ui);
+                        self.table(ui, &mut reader);
                     });
+
+                if filter != self.filter {
+                    if self.source.is_local() {
+                        ctx.data_mut(|data| data.insert_persisted(id, filter.clone()));
+                    }
+                    self.filter = filter;
+                    reload = true;
+                }
             });
 
         self.open = open;
+        if open {
+            self.reader = Some(reader);
+            if reload {
+                self.reload();
+            }
+        }
     }
 
-    fn table(&self, ui: &mut Ui) {
+    fn export_menu(&mut self, ui: &mut Ui) {
+        let exporting = matches!(&self.export, Some(export) if export.ready().is_none());
+        ui.add_enabled_ui(!exporting, |ui| {
+            ui.menu_button("Export…", |ui| {
+                for format in ExportFormat::ALL {
+                    if ui.button(format.to_string()).clicked() {
+                        ui.close_menu();
+                        self.export(format);
+                    }
+                }
+            });
+        });
+        match self.export.as_ref().map(Promise::ready) {
+            Some(None) => {
+                ui.spinner();
+            }
+            Some(Some(Ok(count))) => {
+                ui.weak(format!("Exported {count} entries"));
+            }
+            Some(Some(Err(err))) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+            }
+            None => (),
+        }
+    }
+
+    /// Writes what the filter lets through, not just the window, to a file
+    /// the user picks.
+    fn export(&mut self, format: ExportFormat) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(&format!("journal.{}", format.extension()))
+            .add_filter(&format.to_string(), &[format.extension()])
+            .save_file()
+        else {
+            return;
+        };
+        let source = self.source.clone();
+        let filter = self.filter.clone();
+        self.export = Some(Promise::spawn_blocking(move || {
+            export::export(&source, &filter, format, &path)
+        }));
+    }
+
+    fn table(&mut self, ui: &mut Ui, reader: &mut JournalReader) {
         let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
-        egui_extras::TableBuilder::new(ui)
+        let mut builder = egui_extras::TableBuilder::new(ui).stick_to_bottom(self.follow);
+        if let Some(row) = self.scroll_to.take() {
+            builder = builder.scroll_to_row(row, Some(egui::Align::TOP));
+        }
+        // The rows shown this frame, to page in more when reaching an end.
+        let mut shown: Option<(usize, usize)> = None;
+        builder
             .column(Column::auto())
             .column(Column::auto().at_least(128.0))
             .column(Column::auto().at_least(64.0).resizable(true))
@@ -84,7 +287,7 @@ impl JournalWindow {
                     ui.label("timestamp");
                 });
                 header.col(|ui| {
-                    ui.label("service");
+                    ui.label("unit");
                 });
                 header.col(|ui| {
                     ui.label("message");
@@ -94,31 +297,56 @@ impl JournalWindow {
                 });
             })
             .body(|b| {
-                b.rows(text_height * 2.0, self.filtered.len(), |index, mut row| {
-                    let entry = &self.reader[self.filtered[index]];
-                    row.col(|ui| {
+                b.rows(text_height * 2.0, reader.len(), |index, mut row| {
+                    shown = Some(match shown {
+                        Some((first, last)) => (first.min(index), last.max(index)),
+                        None => (index, index),
+                    });
+                    let entry = &reader[index];
+                    let mut cells = vec![];
+                    cells.push(row.col(|ui| {
                         ui.style_mut().wrap = Some(false);
                         ui.add(entry.priority());
-                    });
+                    }));
 
-                    row.col(|ui| {
+                    cells.push(row.col(|ui| {
                         ui.style_mut().wrap = Some(false);
                         ui.label(entry.timestamp().to_rfc2822());
-                    });
-                    row.col(|ui| {
+                    }));
+                    cells.push(row.col(|ui| {
                         ui.style_mut().wrap = Some(false);
                         ui.label(entry.unit());
-                    });
-                    row.col(|ui| {
+                    }));
+                    cells.push(row.col(|ui| {
                         ui.style_mut().wrap = Some(false);
-                        ui.label(entry.message());
-                    });
-                    row.col(|ui| {
+                        match self.search.ranges(index) {
+                            Some(ranges) => ui.label(journal_search::highlighted(
+                                ui,
+                                entry.message(),
+                                ranges,
+                                self.search.is_current(index),
+                            )),
+                            None => ui.label(entry.message()),
+                        };
+                    }));
+                    cells.push(row.col(|ui| {
                         ui.style_mut().wrap = Some(false);
                         ui.label(entry.kind());
-                    });
+                    }));
+                    let clicked = cells
+                        .into_iter()
+                        .any(|(_, cell)| cell.interact(Sense::click()).clicked());
+                    if clicked {
+                        self.selected = Some(entry.clone());
+                    }
                 })
             });
+
+        match shown {
+            Some((0, _)) if !reader.at_head() => reader.load_older(),
+            Some((_, last)) if last + 1 == reader.len() => reader.load_newer(),
+            _ => (),
+        }
     }
 }
 
@@ -0,0 +1,49 @@
+use egui::{Sense, Ui};
+
+use crate::journal::{Entry, JournalFilter};
+
+/// Every field of `entry`; clicking one filters the journal on it. Returns
+/// whether the pane was closed.
+pub fn draw(ui: &mut Ui, entry: &Entry, filter: &mut JournalFilter) -> bool {
+    let mut close = false;
+    ui.horizontal(|ui| {
+        ui.strong(entry.timestamp().to_rfc2822());
+        close = ui.button("Close").clicked();
+    });
+    ui.weak("Click a field to filter on it.");
+    ui.separator();
+
+    egui::ScrollArea::vertical()
+        .id_source("journal_entry")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("journal_entry_fields")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (field, value) in entry.fields() {
+                        let term = (field.clone(), value.clone());
+                        let name = ui.add(
+                            egui::Label::new(egui::RichText::new(field).monospace())
+                                .sense(Sense::click()),
+                        );
+                        let value = ui.add(
+                            egui::Label::new(value.as_str())
+                                .wrap(true)
+                                .sense(Sense::click()),
+                        );
+                        let filtered = filter.fields.contains(&term);
+                        let response = (name | value).on_hover_text(if filtered {
+                            "Already filtered on"
+                        } else {
+                            "Filter on this field"
+                        });
+                        if response.clicked() && !filtered {
+                            filter.fields.push(term);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    close
+}
@@ -0,0 +1,238 @@
+use egui::{Color32, Ui};
+use poll_promise::Promise;
+
+use crate::error::Error;
+use crate::format;
+use crate::journal::{self, Boot, JournalFilter, JournalSource, Priority};
+
+/// What can be typed into the filter bar, and the field it matches; units
+/// have their own list, as they match several fields.
+const KINDS: [(&str, Option<&str>); 4] = [
+    ("unit", None),
+    ("identifier", Some("SYSLOG_IDENTIFIER")),
+    ("command", Some("_COMM")),
+    ("slice", Some("_SYSTEMD_SLICE")),
+];
+
+/// A removable chip, returns whether it was clicked to remove it.
+fn chip(ui: &mut Ui, text: String) -> bool {
+    ui.add(egui::Button::new(format!("{text} ✖")).small().rounding(8.0))
+        .on_hover_text("Remove")
+        .clicked()
+}
+
+/// Edits a [`JournalFilter`], showing what it filters on as chips.
+pub struct FilterBar {
+    source: JournalSource,
+    current_boot: Option<String>,
+    /// Listed the first time the bar is shown, as it reads the whole journal.
+    boots: Option<Promise<Result<Vec<Boot>, Error>>>,
+    /// The index in [`KINDS`] of what `value` is.
+    kind: usize,
+    value: String,
+    since: String,
+    until: String,
+    error: Option<&'static str>,
+}
+
+impl FilterBar {
+    pub fn new(source: JournalSource) -> Self {
+        Self {
+            current_boot: source.is_local().then(journal::current_boot).flatten(),
+            source,
+            boots: None,
+            kind: 0,
+            value: String::new(),
+            since: String::new(),
+            until: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn draw(&mut self, ui: &mut Ui, filter: &mut JournalFilter) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("journal_priority")
+                .selected_text(match filter.priority {
+                    Some(priority) => format!("{priority} and above"),
+                    None => "any priority".to_owned(),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut filter.priority, None, "any priority");
+                    for priority in Priority::ALL {
+                        ui.selectable_value(
+                            &mut filter.priority,
+                            Some(priority),
+                            priority.to_string(),
+                        );
+                    }
+                });
+
+            self.boot(ui, filter);
+
+            ui.separator();
+            egui::ComboBox::from_id_source("journal_filter_kind")
+                .selected_text(KINDS[self.kind].0)
+                .show_ui(ui, |ui| {
+                    for (index, (label, _)) in KINDS.iter().enumerate() {
+                        ui.selectable_value(&mut self.kind, index, *label);
+                    }
+                });
+            let input = ui.text_edit_singleline(&mut self.value);
+            let entered = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui.button("Add").clicked() || entered) && !self.value.trim().is_empty() {
+                let value = self.value.trim().to_owned();
+                match KINDS[self.kind].1 {
+                    None if !filter.units.contains(&value) => filter.units.push(value),
+                    Some(field) => {
+                        let term = (field.to_owned(), value);
+                        if !filter.fields.contains(&term) {
+                            filter.fields.push(term);
+                        }
+                    }
+                    None => (),
+                }
+                self.value.clear();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            for (label, input, bound) in [
+                ("Since:", &mut self.since, &mut filter.since),
+                ("Until:", &mut self.until, &mut filter.until),
+            ] {
+                ui.label(label);
+                let response = ui.add(
+                    egui::TextEdit::singleline(input)
+                        .hint_text("2023-07-01 12:00, yesterday, -1h")
+                        .desired_width(160.0),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    match format::parse_timestamp(input) {
+                        Ok(usec) => {
+                            *bound = Some(usec);
+                            input.clear();
+                            self.error = None;
+                        }
+                        Err(err) => self.error = Some(err),
+                    }
+                }
+            }
+            if let Some(err) = self.error {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+            }
+        });
+
+        chips(ui, filter);
+    }
+
+    fn boot(&mut self, ui: &mut Ui, filter: &mut JournalFilter) {
+        if self.boots.is_none() {
+            let source = self.source.clone();
+            self.boots = Some(Promise::spawn_blocking(move || {
+                journal::list_boots(&source)
+            }));
+        }
+        let listed = self.boots.as_ref().and_then(Promise::ready);
+        if listed.is_none() {
+            ui.ctx().request_repaint();
+        }
+        let boots = match listed {
+            Some(Ok(boots)) => boots.as_slice(),
+            Some(Err(_)) | None => &[],
+        };
+        let current = self.current_boot.as_ref();
+        // Counting back from the newest, as `journalctl --boot` does.
+        let offset = |id: &String| {
+            boots
+                .iter()
+                .rev()
+                .position(|boot| &boot.id == id)
+                .map(|offset| format!("boot -{offset}"))
+        };
+        let previous = match current.and_then(|id| boots.iter().position(|boot| &boot.id == id)) {
+            Some(index) => index.checked_sub(1).map(|index| &boots[index]),
+            // The current boot has logged nothing yet.
+            None if self.source.is_local() => boots.last(),
+            // Another machine's journal, where no boot is the current one.
+            None => None,
+        };
+
+        egui::ComboBox::from_id_source("journal_boot")
+            .selected_text(match &filter.boot {
+                None => "all boots".to_owned(),
+                Some(boot) if Some(boot) == current => "current boot".to_owned(),
+                Some(boot) if Some(boot) == previous.map(|previous| &previous.id) => {
+                    "previous boot".to_owned()
+                }
+                Some(boot) => offset(boot).unwrap_or_else(|| "another boot".to_owned()),
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter.boot, None, "all boots");
+                if let Some(current) = current {
+                    ui.selectable_value(&mut filter.boot, Some(current.clone()), "current boot");
+                }
+                if let Some(previous) = previous {
+                    ui.selectable_value(
+                        &mut filter.boot,
+                        Some(previous.id.clone()),
+                        "previous boot",
+                    );
+                }
+                match listed {
+                    None => {
+                        ui.spinner();
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                    }
+                    Some(Ok(_)) => {
+                        ui.separator();
+                        for (offset, boot) in boots.iter().rev().enumerate() {
+                            ui.selectable_value(
+                                &mut filter.boot,
+                                Some(boot.id.clone()),
+                                format!(
+                                    "-{offset} {} {} — {}",
+                                    &boot.id[..boot.id.len().min(8)],
+                                    boot.first.format("%Y-%m-%d %T"),
+                                    boot.last.format("%Y-%m-%d %T"),
+                                ),
+                            );
+                        }
+                    }
+                }
+            });
+    }
+}
+
+fn chips(ui: &mut Ui, filter: &mut JournalFilter) {
+    ui.horizontal_wrapped(|ui| {
+        filter
+            .units
+            .retain(|unit| !chip(ui, format!("unit: {unit}")));
+        if let Some(priority) = filter.priority {
+            if chip(ui, format!("priority: {priority} and above")) {
+                filter.priority = None;
+            }
+        }
+        if let Some(boot) = &filter.boot {
+            if chip(ui, format!("boot: {boot}")) {
+                filter.boot = None;
+            }
+        }
+        filter.fields.retain(|(field, value)| {
+            let label = KINDS
+                .iter()
+                .find(|(_, kind)| *kind == Some(field.as_str()))
+                .map_or(field.as_str(), |(label, _)| label);
+            !chip(ui, format!("{label}: {value}"))
+        });
+        for (label, bound) in [("since", &mut filter.since), ("until", &mut filter.until)] {
+            if let Some(usec) = *bound {
+                if chip(ui, format!("{label}: {}", format::timestamp(usec))) {
+                    *bound = None;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,191 @@
+use std::ops::Range;
+
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, Ui};
+use poll_promise::Promise;
+
+use crate::journal::JournalReader;
+use crate::search::{self, Hits, SearchMode};
+
+/// Searches the messages the journal window has, and steps through the hits.
+pub struct SearchBar {
+    query: String,
+    mode: SearchMode,
+    running: Option<Promise<Hits>>,
+    hits: Hits,
+    /// The index in `hits.rows` of the hit last jumped to.
+    current: Option<usize>,
+    /// The cursor of that hit's entry, to find it again once the rows moved.
+    current_cursor: Option<String>,
+    error: Option<String>,
+}
+
+impl SearchBar {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            mode: SearchMode::Plain,
+            running: None,
+            hits: Hits::default(),
+            current: None,
+            current_cursor: None,
+            error: None,
+        }
+    }
+
+    /// Searches again, as the rows of `reader` changed or the query did.
+    pub fn restart(&mut self, reader: &JournalReader) {
+        self.error = None;
+        self.running = None;
+        // The rows of the old hits may not be the same entries any more.
+        self.hits = Hits::default();
+        self.current = None;
+        if self.query.is_empty() {
+            return;
+        }
+        let matcher = match search::matcher(&self.query, self.mode) {
+            Ok(matcher) => matcher,
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return;
+            }
+        };
+        let messages: Vec<String> = (0..reader.len())
+            .map(|row| reader[row].message().to_owned())
+            .collect();
+        self.running = Some(Promise::spawn_blocking(move || {
+            search::search(&matcher, &messages)
+        }));
+    }
+
+    /// Returns the row to scroll to, when going to another hit.
+    pub fn draw(&mut self, ui: &mut Ui, reader: &JournalReader) -> Option<usize> {
+        if let Some(running) = self.running.take() {
+            match running.try_take() {
+                Ok(hits) => {
+                    self.current = self.current_cursor.as_ref().and_then(|cursor| {
+                        hits.rows.iter().position(|&row| {
+                            row < reader.len() && reader[row].cursor() == cursor.as_str()
+                        })
+                    });
+                    self.hits = hits;
+                }
+                Err(running) => {
+                    self.running = Some(running);
+                    ui.ctx().request_repaint();
+                }
+            }
+        }
+
+        let mut step: Option<bool> = None;
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            let input = ui.add(
+                egui::TextEdit::singleline(&mut self.query)
+                    .hint_text("text in the messages")
+                    .desired_width(240.0),
+            );
+            let mut changed = input.changed();
+            if input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                step = Some(!ui.input(|i| i.modifiers.shift));
+                input.request_focus();
+            }
+            egui::ComboBox::from_id_source("journal_search_mode")
+                .selected_text(self.mode.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in SearchMode::ALL {
+                        changed |= ui
+                            .selectable_value(&mut self.mode, mode, mode.to_string())
+                            .changed();
+                    }
+                });
+            if changed {
+                self.current_cursor = None;
+                self.restart(reader);
+            }
+
+            let has_hits = !self.hits.rows.is_empty();
+            if ui
+                .add_enabled(has_hits, egui::Button::new("Previous"))
+                .clicked()
+            {
+                step = Some(false);
+            }
+            if ui
+                .add_enabled(has_hits, egui::Button::new("Next"))
+                .clicked()
+            {
+                step = Some(true);
+            }
+
+            if self.running.is_some() {
+                ui.spinner();
+            }
+            if let Some(err) = &self.error {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+            } else if !self.query.is_empty() && self.running.is_none() {
+                match (self.current, self.hits.rows.len()) {
+                    (_, 0) => ui.weak("no hits"),
+                    (Some(current), count) => ui.label(format!("{}/{count} hits", current + 1)),
+                    (None, count) => ui.label(format!("{count} hits")),
+                };
+            }
+        });
+
+        let count = self.hits.rows.len();
+        let forward = step?;
+        if count == 0 {
+            return None;
+        }
+        let current = match (self.current, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(current), true) => (current + 1) % count,
+            (Some(current), false) => (current + count - 1) % count,
+        };
+        let row = self.hits.rows[current];
+        self.current = Some(current);
+        self.current_cursor = Some(reader[row].cursor().to_owned());
+        Some(row)
+    }
+
+    /// Where `row`'s message matches, if it does.
+    pub fn ranges(&self, row: usize) -> Option<&[Range<usize>]> {
+        self.hits.ranges.get(&row).map(Vec::as_slice)
+    }
+
+    pub fn is_current(&self, row: usize) -> bool {
+        matches!(self.current, Some(current) if self.hits.rows.get(current) == Some(&row))
+    }
+}
+
+/// `text` with `ranges` marked, the current hit more than the others.
+pub fn highlighted(ui: &Ui, text: &str, ranges: &[Range<usize>], current: bool) -> LayoutJob {
+    let format = TextFormat {
+        font_id: egui::TextStyle::Body.resolve(ui.style()),
+        color: ui.visuals().text_color(),
+        ..Default::default()
+    };
+    let marked = TextFormat {
+        color: Color32::BLACK,
+        background: if current {
+            Color32::GOLD
+        } else {
+            Color32::KHAKI
+        },
+        ..format.clone()
+    };
+    let mut job = LayoutJob::default();
+    let mut at = 0;
+    for range in ranges {
+        // Ranges from another text than this are left out.
+        let (Some(before), Some(hit)) = (text.get(at..range.start), text.get(range.clone())) else {
+            continue;
+        };
+        job.append(before, 0.0, format.clone());
+        job.append(hit, 0.0, marked.clone());
+        at = range.end;
+    }
+    job.append(&text[at..], 0.0, format);
+    job
+}
@@ -0,0 +1,198 @@
+use std::path::Path;
+
+use egui::{Color32, Context, Ui};
+use egui_extras::Column;
+use poll_promise::Promise;
+
+use crate::procfs::SysRoot;
+use crate::systemd;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LookupKind {
+    Port,
+    File,
+    Pid,
+}
+
+struct Owner {
+    pid: u32,
+    /// What linked the process to the query, e.g. `tcp6` for a port.
+    via: String,
+    command_line: String,
+    unit: Option<String>,
+}
+
+/// Answers "which unit owns this port / file / PID".
+pub struct Lookup {
+    open: bool,
+    kind: LookupKind,
+    query: String,
+    root: SysRoot,
+    owners: Vec<Owner>,
+    error: Option<String>,
+}
+
+impl Default for Lookup {
+    fn default() -> Self {
+        Self {
+            open: false,
+            kind: LookupKind::Port,
+            query: String::new(),
+            root: SysRoot::default(),
+            owners: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+impl Lookup {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Returns the unit whose properties should be opened, if one was picked.
+    pub fn draw(&mut self, ctx: &Context, con: &zbus::Connection) -> Option<String> {
+        let mut picked = None;
+        let mut open = self.open;
+        egui::Window::new("Which unit owns…")
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.kind, LookupKind::Port, "Port");
+                    ui.radio_value(&mut self.kind, LookupKind::File, "File");
+                    ui.radio_value(&mut self.kind, LookupKind::Pid, "PID");
+                });
+                ui.horizontal(|ui| {
+                    let edit = ui.text_edit_singleline(&mut self.query);
+                    let submitted =
+                        edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Look up").clicked() || submitted {
+                        self.lookup(con);
+                    }
+                });
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                }
+                picked = self.table(ui);
+            });
+        self.open = open;
+        picked
+    }
+
+    fn lookup(&mut self, con: &zbus::Connection) {
+        self.owners.clear();
+        self.error = None;
+        let query = self.query.trim();
+
+        let pids: Result<Vec<(u32, String)>, String> = match self.kind {
+            LookupKind::Port => query
+                .trim_start_matches(':')
+                .parse::<u16>()
+                .map_err(|_| "not a port number".to_owned())
+                .and_then(|port| self.pids_on_port(port).map_err(|err| err.to_string())),
+            LookupKind::File => {
+                let target = Path::new(query);
+                self.root
+                    .pids_with_fd(|link| link == target)
+                    .map(|pids| {
+                        pids.into_iter()
+                            .map(|pid| (pid, "open".to_owned()))
+                            .collect()
+                    })
+                    .map_err(|err| err.to_string())
+            }
+            LookupKind::Pid => query
+                .parse::<u32>()
+                .map(|pid| vec![(pid, "pid".to_owned())])
+                .map_err(|_| "not a PID".to_owned()),
+        };
+
+        match pids {
+            Ok(pids) if pids.is_empty() => self.error = Some("nothing found".to_owned()),
+            Ok(pids) => {
+                for (pid, via) in pids {
+                    self.owners.push(Owner {
+                        pid,
+                        via,
+                        command_line: self
+                            .root
+                            .process(pid)
+                            .map(|process| process.command_line)
+                            .unwrap_or_default(),
+                        unit: self.unit_of(con, pid),
+                    });
+                }
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    fn pids_on_port(&self, port: u16) -> std::io::Result<Vec<(u32, String)>> {
+        let mut owners = Vec::new();
+        for (table, inode) in self.root.sockets_on_port(port)? {
+            let link = format!("socket:[{inode}]");
+            for pid in self
+                .root
+                .pids_with_fd(|target| target == Path::new(&link))?
+            {
+                if !owners.iter().any(|(p, t)| *p == pid && t == table) {
+                    owners.push((pid, table.to_owned()));
+                }
+            }
+        }
+        Ok(owners)
+    }
+
+    fn unit_of(&self, con: &zbus::Connection, pid: u32) -> Option<String> {
+        Promise::spawn_async(systemd::unit_by_pid(con.clone(), pid))
+            .block_and_take()
+            .ok()
+            .or_else(|| self.root.unit_of_pid(pid).ok().flatten())
+    }
+
+    fn table(&self, ui: &mut Ui) -> Option<String> {
+        let mut picked = None;
+        let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
+        egui_extras::TableBuilder::new(ui)
+            .striped(true)
+            .column(Column::auto().at_least(64.0))
+            .column(Column::auto().at_least(48.0))
+            .column(Column::auto().at_least(128.0))
+            .column(Column::remainder())
+            .header(text_height, |mut header| {
+                for title in ["PID", "via", "unit", "command"] {
+                    header.col(|ui| {
+                        ui.label(title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(text_height * 1.5, self.owners.len(), |index, mut row| {
+                    let owner = &self.owners[index];
+                    row.col(|ui| {
+                        ui.label(owner.pid.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(&owner.via);
+                    });
+                    row.col(|ui| match &owner.unit {
+                        Some(unit) => {
+                            if ui.link(unit).clicked() {
+                                picked = Some(unit.clone());
+                            }
+                        }
+                        None => {
+                            ui.label("-");
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.style_mut().wrap = Some(false);
+                        ui.label(&owner.command_line);
+                    });
+                });
+            });
+        picked
+    }
+}
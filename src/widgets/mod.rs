@@ -1,6 +1,23 @@
+pub mod all_properties;
+pub mod boot;
+pub mod boot_timeline;
+pub mod clean;
+pub mod dependency_graph;
+pub mod exec;
+pub mod failed;
+pub mod failure;
+pub mod hardening;
 pub mod journal;
+pub mod journal_entry;
+pub mod journal_filter;
+pub mod journal_search;
+pub mod lookup;
+pub mod processes;
 pub mod properties;
+pub mod resource_control;
+pub mod security;
 pub mod services;
+pub mod stop;
 pub mod system_overview;
 pub mod unitdata;
 pub mod units_table;
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use egui::{Color32, Ui};
+use egui_extras::Column;
+use poll_promise::Promise;
+
+use crate::format;
+use crate::procfs::{ProcessInfo, SysRoot};
+use crate::signal::{self, SIGNALS};
+use crate::systemd::{self, UnitData};
+
+/// Lists the processes in a unit's control group and sends signals to them.
+#[derive(Clone)]
+pub struct ProcessList {
+    unit: Option<String>,
+    root: SysRoot,
+    processes: Vec<ProcessInfo>,
+    users: HashMap<u32, String>,
+    selected: Option<u32>,
+    signal: i32,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for ProcessList {
+    fn default() -> Self {
+        Self {
+            unit: None,
+            root: SysRoot::default(),
+            processes: Vec::new(),
+            users: HashMap::new(),
+            selected: None,
+            signal: libc::SIGTERM,
+            status: None,
+        }
+    }
+}
+
+impl ProcessList {
+    pub fn draw(&mut self, ui: &mut Ui, con: &zbus::Connection, unit: &UnitData) {
+        if self.unit.as_deref() != Some(unit.name.as_str()) {
+            *self = Self {
+                unit: Some(unit.name.clone()),
+                ..Default::default()
+            };
+            self.refresh(con, unit);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Refresh").clicked() {
+                self.refresh(con, unit);
+            }
+            egui::ComboBox::from_id_source("process_signal")
+                .selected_text(signal::signal_name(self.signal).unwrap_or("?"))
+                .show_ui(ui, |ui| {
+                    for (number, name) in SIGNALS {
+                        ui.selectable_value(&mut self.signal, number, name);
+                    }
+                });
+            let send = ui.add_enabled(self.selected.is_some(), egui::Button::new("Send"));
+            if let (true, Some(pid)) = (send.clicked(), self.selected) {
+                let name = signal::signal_name(self.signal).unwrap_or("?");
+                self.status = Some(match signal::kill(pid, self.signal) {
+                    Ok(()) => Ok(format!("Sent {name} to {pid}.")),
+                    Err(err) => Err(format!("{name} to {pid}: {err}")),
+                });
+            }
+        });
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+            }
+            None => (),
+        }
+
+        self.table(ui);
+    }
+
+    fn refresh(&mut self, con: &zbus::Connection, unit: &UnitData) {
+        let Some(interface) = systemd::has_cgroup(&unit.name)
+            .then(|| systemd::unit_type_interface(&unit.name))
+            .flatten()
+        else {
+            self.status = Some(Err("this unit type has no processes".to_owned()));
+            return;
+        };
+
+        let pids: Result<Vec<u32>, String> = match Promise::spawn_async(systemd::unit_processes(
+            con.clone(),
+            unit.object_path.clone(),
+            interface,
+        ))
+        .block_and_take()
+        {
+            Ok(processes) => Ok(processes.into_iter().map(|(_, pid, _)| pid).collect()),
+            // Older managers lack GetProcesses, ask the cgroup filesystem.
+            Err(_) => Promise::spawn_async(systemd::control_group(
+                con.clone(),
+                unit.object_path.clone(),
+                interface,
+            ))
+            .block_and_take()
+            .map_err(|err| err.to_string())
+            .and_then(|cgroup| {
+                self.root
+                    .cgroup_procs(&cgroup)
+                    .map_err(|err| format!("{cgroup}: {err}"))
+            }),
+        };
+
+        match pids {
+            Ok(pids) => {
+                // Processes may exit in the meantime, those are just skipped.
+                self.processes = pids
+                    .iter()
+                    .filter_map(|pid| self.root.process(*pid).ok())
+                    .collect();
+                for uid in self.processes.iter().filter_map(|p| p.uid) {
+                    if !self.users.contains_key(&uid) {
+                        let name = self.root.user_name(uid).unwrap_or_else(|| uid.to_string());
+                        self.users.insert(uid, name);
+                    }
+                }
+                if !matches!(self.selected, Some(pid) if pids.contains(&pid)) {
+                    self.selected = None;
+                }
+            }
+            Err(err) => self.status = Some(Err(err)),
+        }
+    }
+
+    fn table(&mut self, ui: &mut Ui) {
+        let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
+        egui_extras::TableBuilder::new(ui)
+            .striped(true)
+            .column(Column::auto().at_least(64.0))
+            .column(Column::auto().at_least(64.0))
+            .column(Column::auto().at_least(64.0))
+            .column(Column::auto().at_least(128.0))
+            .column(Column::remainder())
+            .header(text_height, |mut header| {
+                for title in ["PID", "user", "RSS", "started", "command"] {
+                    header.col(|ui| {
+                        ui.label(title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(text_height * 1.5, self.processes.len(), |index, mut row| {
+                    let process = &self.processes[index];
+                    row.col(|ui| {
+                        ui.radio_value(
+                            &mut self.selected,
+                            Some(process.pid),
+                            process.pid.to_string(),
+                        );
+                    });
+                    row.col(|ui| {
+                        if let Some(user) = process.uid.and_then(|uid| self.users.get(&uid)) {
+                            ui.label(user);
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(process.rss.map(format::bytes).unwrap_or_default());
+                    });
+                    row.col(|ui| {
+                        ui.label(process.started.map(format::date).unwrap_or_default());
+                    });
+                    row.col(|ui| {
+                        ui.style_mut().wrap = Some(false);
+                        ui.label(&process.command_line);
+                    });
+                });
+            });
+    }
+}
@@ -1,24 +1,62 @@
+use super::all_properties::AllProperties;
+use super::clean::CleanDialog;
+use super::dependency_graph::DependencyGraph;
+use super::exec::ExecPanel;
+use super::failure::FailureAnalysis;
+use super::hardening::HardeningAssistant;
+use super::processes::ProcessList;
+use super::resource_control::ResourceControl;
+use super::security::SecurityPanel;
+use super::stop::StopDialog;
 use super::unitdata::{ActiveStateLabel, LoadStateLabel, UnitFilePresetLabel, UnitFileStateLabel};
-use crate::systemd::{self, ActiveState, LoadState, UnitFilePreset, UnitFileState};
+use crate::systemd::{
+    self, ActiveState, FreezerState, LoadState, UnitFilePreset, UnitFileState, CLEAN_SINCE,
+    FREEZE_SINCE,
+};
+use ::systemd::journal::OpenOptions;
 use egui::{Color32, Context, Label, Ui, Widget, Window};
 use poll_promise::Promise;
 use zbus_systemd::systemd1::{ManagerProxy, UnitProxy};
 use zvariant::OwnedObjectPath;
 
-#[derive(Clone)]
 pub struct PropertiesWindow {
     unit: Option<usize>,
     open: bool,
+    resources: ResourceControl,
+    processes: ProcessList,
+    failure: FailureAnalysis,
+    exec: ExecPanel,
+    security: SecurityPanel,
+    all: AllProperties,
+    clean: Option<CleanDialog>,
+    dependencies: Option<DependencyGraph>,
+    stop: Option<StopDialog>,
+    hardening: Option<HardeningAssistant>,
+    /// Major version of the manager, to hide what it doesn't support.
+    version: Option<u32>,
 
     con: zbus::Connection,
+    options: OpenOptions,
 }
 
 impl PropertiesWindow {
-    pub fn with_connection(con: zbus::Connection) -> PropertiesWindow {
+    pub fn with_connection(con: zbus::Connection, options: OpenOptions) -> PropertiesWindow {
         Self {
             unit: None,
             open: false,
+            resources: ResourceControl::default(),
+            processes: ProcessList::default(),
+            failure: FailureAnalysis::default(),
+            exec: ExecPanel::default(),
+            security: SecurityPanel::default(),
+            all: AllProperties::default(),
+            clean: None,
+            dependencies: None,
+            stop: None,
+            hardening: None,
+            version: None,
             con,
+            options,
         }
     }
 
@@ -31,6 +69,13 @@ impl PropertiesWindow {
                 let unit_opt = extractor(index);
 
                 let mut open = self.open;
+                let version = self.manager_version();
+                let mut resources = std::mem::take(&mut self.resources);
+                let mut processes = std::mem::take(&mut self.processes);
+                let mut failure = std::mem::take(&mut self.failure);
+                let mut exec = std::mem::take(&mut self.exec);
+                let mut security = std::mem::take(&mut self.security);
+                let mut all = std::mem::take(&mut self.all);
                 if let Some(unit) = unit_opt {
                     Window::new("Service Properties")
                         .resizable(true)
@@ -44,6 +89,7 @@ impl PropertiesWindow {
                             match Promise::spawn_async(build_ui(
                                 self.con.clone(),
                                 unit.object_path.clone(),
+                                version,
                             ))
                             .block_and_take()
                             {
@@ -63,18 +109,66 @@ impl PropertiesWindow {
                                 }
                             }
 
-                            let result =
-                                self.build_buttons(ui, unit.object_path.clone(), unit.name.clone());
+                            let result = self.build_buttons(ui, unit, version);
                             if let Err(err) = result {
                                 ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
                                 println!("ERROR at {}:{}: {err}", file!(), line!());
                             }
+
+                            failure.draw(ui, &self.con, &self.options, unit);
+
+                            if unit.name.ends_with(".service") {
+                                egui::CollapsingHeader::new("Execution").show(ui, |ui| {
+                                    exec.draw(ui, &self.con, unit);
+                                });
+                            }
+                            egui::CollapsingHeader::new("Resource control").show(ui, |ui| {
+                                resources.draw(ui, &self.con, unit);
+                            });
+                            egui::CollapsingHeader::new("Processes").show(ui, |ui| {
+                                processes.draw(ui, &self.con, unit);
+                            });
+                            egui::CollapsingHeader::new("Security").show(ui, |ui| {
+                                if let Some(hardening) = security.draw(ui, &self.con, unit) {
+                                    self.hardening = Some(hardening);
+                                }
+                            });
+                            egui::CollapsingHeader::new("All properties").show(ui, |ui| {
+                                all.draw(ui, &self.con, unit);
+                            });
                         });
                 }
+                self.resources = resources;
+                self.processes = processes;
+                self.failure = failure;
+                self.exec = exec;
+                self.security = security;
+                self.all = all;
                 self.open = open;
             }
             None => return,
         };
+        if let Some(clean) = &mut self.clean {
+            if !clean.draw(ctx, &self.con) {
+                self.clean = None;
+            }
+        }
+        if let Some(stop) = &mut self.stop {
+            let version = self.version.unwrap_or_default();
+            if !stop.draw(ctx, &self.con, version) {
+                self.stop = None;
+            }
+        }
+        if let Some(graph) = &mut self.dependencies {
+            if !graph.draw(ctx, &self.con) {
+                self.dependencies = None;
+            }
+        }
+        if let Some(hardening) = &mut self.hardening {
+            if !hardening.draw(ctx, &self.con, &self.options) {
+                self.hardening = None;
+            }
+        }
         if !self.open {
             self.unit = None
         }
@@ -87,9 +181,30 @@ impl PropertiesWindow {
     pub fn close(&mut self) {
         self.unit = None;
         self.open = false;
+        self.clean = None;
+        self.dependencies = None;
+        self.stop = None;
+        self.hardening = None;
     }
 
-    fn build_buttons(&self, ui: &mut Ui, path: OwnedObjectPath, name: String) -> zbus::Result<()> {
+    fn manager_version(&mut self) -> u32 {
+        if self.version.is_none() {
+            let version =
+                Promise::spawn_async(systemd::manager_version(self.con.clone())).block_and_take();
+            // Assume an old manager when unsure, to only offer what surely works.
+            self.version = Some(version.unwrap_or(0));
+        }
+        self.version.unwrap_or(0)
+    }
+
+    fn build_buttons(
+        &mut self,
+        ui: &mut Ui,
+        unit: &systemd::UnitData,
+        version: u32,
+    ) -> zbus::Result<()> {
+        let path = unit.object_path.clone();
+        let name = unit.name.clone();
         if ui.button("Restart Unit").clicked() {
             self.restart(path.clone())?;
         }
@@ -98,7 +213,29 @@ impl PropertiesWindow {
                 self.start(path.clone())?;
             }
         } else if ui.button("Stop Unit").clicked() {
-            self.stop(path.clone())?;
+            self.stop = Some(StopDialog::new(&self.con, unit));
+        }
+
+        if version >= FREEZE_SINCE && self.can_freeze(path.clone())? {
+            let freezer = self.freezer_state(path.clone())?;
+            if freezer.can_freeze() {
+                if ui.button("Freeze Unit").clicked() {
+                    self.call_unit(path.clone(), "Freeze")?;
+                }
+            } else if freezer == FreezerState::Frozen && ui.button("Thaw Unit").clicked() {
+                self.call_unit(path.clone(), "Thaw")?;
+            }
+        }
+
+        if version >= CLEAN_SINCE {
+            let cleanable = self.can_clean(path.clone())?;
+            if !cleanable.is_empty() && ui.button("Clean…").clicked() {
+                self.clean = Some(CleanDialog::new(&self.con, unit, cleanable));
+            }
+        }
+
+        if ui.button("Dependencies…").clicked() {
+            self.dependencies = Some(DependencyGraph::new(name.clone()));
         }
 
         let ufs = self.unit_file_state(path)?;
@@ -111,6 +248,53 @@ impl PropertiesWindow {
         Ok(())
     }
 
+    fn can_freeze(&self, path: OwnedObjectPath) -> zbus::Result<bool> {
+        let con = self.con.clone();
+        Promise::spawn_async(async move {
+            systemd::unit_interface_proxy(&con, path, systemd::UNIT_INTERFACE)
+                .await?
+                .get_property("CanFreeze")
+                .await
+        })
+        .block_and_take()
+    }
+
+    fn freezer_state(&self, path: OwnedObjectPath) -> zbus::Result<FreezerState> {
+        let con = self.con.clone();
+        Promise::spawn_async(async move {
+            Ok(FreezerState::from(
+                systemd::unit_interface_proxy(&con, path, systemd::UNIT_INTERFACE)
+                    .await?
+                    .get_property::<String>("FreezerState")
+                    .await?,
+            ))
+        })
+        .block_and_take()
+    }
+
+    fn can_clean(&self, path: OwnedObjectPath) -> zbus::Result<Vec<String>> {
+        let con = self.con.clone();
+        Promise::spawn_async(async move {
+            systemd::unit_interface_proxy(&con, path, systemd::UNIT_INTERFACE)
+                .await?
+                .get_property("CanClean")
+                .await
+        })
+        .block_and_take()
+    }
+
+    /// Calls an argument-less method of the Unit interface, like `Freeze`.
+    fn call_unit(&self, path: OwnedObjectPath, method: &'static str) -> zbus::Result<()> {
+        let con = self.con.clone();
+        Promise::spawn_async(async move {
+            systemd::unit_interface_proxy(&con, path, systemd::UNIT_INTERFACE)
+                .await?
+                .call(method, &())
+                .await
+        })
+        .block_and_take()
+    }
+
     fn can_start(&self, path: OwnedObjectPath) -> zbus::Result<bool> {
         let con = self.con.clone();
         Promise::spawn_async(async move {
@@ -142,16 +326,6 @@ impl PropertiesWindow {
         })
         .block_and_take()
     }
-    fn stop(&self, path: OwnedObjectPath) -> zbus::Result<OwnedObjectPath> {
-        let con = self.con.clone();
-        Promise::spawn_async(async move {
-            UnitProxy::new(&con, path)
-                .await?
-                .stop("replace".to_owned())
-                .await
-        })
-        .block_and_take()
-    }
     fn unit_file_state(&self, path: OwnedObjectPath) -> zbus::Result<UnitFileState> {
         let con = self.con.clone();
         Promise::spawn_async(async move {
@@ -211,8 +385,8 @@ impl From<Label> for PropertiesWidget {
     }
 }
 
-impl From<ActiveState> for PropertiesWidget {
-    fn from(value: ActiveState) -> Self {
+impl From<ActiveStateLabel> for PropertiesWidget {
+    fn from(value: ActiveStateLabel) -> Self {
         Self::ActiveState(value)
     }
 }
@@ -238,10 +412,25 @@ impl From<UnitFilePreset> for PropertiesWidget {
 async fn build_ui(
     con: zbus::Connection,
     path: OwnedObjectPath,
+    version: u32,
 ) -> zbus::Result<Vec<PropertiesWidget>> {
-    let proxy = zbus_systemd::systemd1::UnitProxy::new(&con, path).await?;
+    let proxy = zbus_systemd::systemd1::UnitProxy::new(&con, path.clone()).await?;
     let mut vec: Vec<PropertiesWidget> = Vec::new();
-    vec.push(ActiveState::from(proxy.active_state().await?).into());
+    let freezer = if version >= FREEZE_SINCE {
+        let unit = systemd::unit_interface_proxy(&con, path, systemd::UNIT_INTERFACE).await?;
+        Some(FreezerState::from(
+            unit.get_property::<String>("FreezerState").await?,
+        ))
+    } else {
+        None
+    };
+    vec.push(
+        ActiveStateLabel {
+            state: ActiveState::from(proxy.active_state().await?),
+            freezer,
+        }
+        .into(),
+    );
     let load_state = LoadState::from(proxy.load_state().await?);
     vec.push(load_state.into());
     if load_state == LoadState::NotFound {
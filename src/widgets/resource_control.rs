@@ -0,0 +1,267 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use egui::plot::{HLine, Legend, Line, Plot, PlotPoints};
+use egui::{Color32, Ui};
+use poll_promise::Promise;
+
+use crate::systemd::{self, ResourceUsage, UnitData, RESOURCE_SETTINGS};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_SAMPLES: usize = 120;
+
+#[derive(Clone, Copy)]
+struct Sample {
+    /// Seconds since the panel was opened for the unit.
+    at: f64,
+    usage: ResourceUsage,
+}
+
+/// Edits the resource-control settings of a running unit and graphs its
+/// usage against them.
+#[derive(Clone, Default)]
+pub struct ResourceControl {
+    unit: Option<String>,
+    interface: Option<&'static str>,
+    limits: Vec<u64>,
+    inputs: Vec<String>,
+    /// The inputs as `reload` filled them in; formatting may round, so only
+    /// the ones edited since are applied.
+    loaded: Vec<String>,
+    runtime: bool,
+    status: Option<Result<String, String>>,
+    samples: VecDeque<Sample>,
+    opened: Option<Instant>,
+    last_sample: Option<Instant>,
+}
+
+impl ResourceControl {
+    pub fn draw(&mut self, ui: &mut Ui, con: &zbus::Connection, unit: &UnitData) {
+        if self.unit.as_deref() != Some(unit.name.as_str()) {
+            self.reset(unit);
+            self.reload(con, unit);
+        }
+        let Some(interface) = self.interface else {
+            ui.label("This unit type has no resource control.");
+            return;
+        };
+
+        if !matches!(self.last_sample, Some(last) if last.elapsed() < SAMPLE_INTERVAL) {
+            self.sample(con, unit, interface);
+        }
+        ui.ctx().request_repaint_after(SAMPLE_INTERVAL);
+
+        egui::Grid::new("resource_control")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (index, setting) in RESOURCE_SETTINGS.iter().enumerate() {
+                    ui.label(setting.directive);
+                    match self.limits.get(index) {
+                        Some(value) => ui.label(setting.kind.format(*value)),
+                        None => ui.spinner(),
+                    };
+                    let valid = setting.kind.parse(&self.inputs[index]).is_ok();
+                    let mut edit =
+                        egui::TextEdit::singleline(&mut self.inputs[index]).desired_width(96.0);
+                    if !valid {
+                        edit = edit.text_color(Color32::RED);
+                    }
+                    ui.add(edit);
+                    ui.end_row();
+                }
+            });
+
+        ui.checkbox(&mut self.runtime, "Runtime only (reverted on reboot)");
+        if ui.button("Apply").clicked() {
+            self.apply(con, unit);
+        }
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+            }
+            None => (),
+        }
+
+        self.graphs(ui);
+    }
+
+    fn reset(&mut self, unit: &UnitData) {
+        *self = Self {
+            unit: Some(unit.name.clone()),
+            interface: systemd::has_cgroup(&unit.name)
+                .then(|| systemd::unit_type_interface(&unit.name))
+                .flatten(),
+            runtime: true,
+            opened: Some(Instant::now()),
+            ..Default::default()
+        };
+    }
+
+    fn reload(&mut self, con: &zbus::Connection, unit: &UnitData) {
+        let Some(interface) = self.interface else {
+            return;
+        };
+        match Promise::spawn_async(systemd::resource_limits(
+            con.clone(),
+            unit.object_path.clone(),
+            interface,
+        ))
+        .block_and_take()
+        {
+            Ok(limits) => {
+                self.inputs = RESOURCE_SETTINGS
+                    .iter()
+                    .zip(&limits)
+                    .map(|(setting, value)| setting.kind.format(*value))
+                    .collect();
+                self.loaded = self.inputs.clone();
+                self.limits = limits;
+            }
+            Err(err) => {
+                self.inputs = vec![String::new(); RESOURCE_SETTINGS.len()];
+                self.loaded = self.inputs.clone();
+                self.status = Some(Err(err.to_string()));
+            }
+        }
+    }
+
+    fn apply(&mut self, con: &zbus::Connection, unit: &UnitData) {
+        let mut changed = Vec::new();
+        for (index, setting) in RESOURCE_SETTINGS.iter().enumerate() {
+            if self.loaded.get(index) == Some(&self.inputs[index]) {
+                continue;
+            }
+            match setting.kind.parse(&self.inputs[index]) {
+                Ok(value) if self.limits.get(index) != Some(&value) => {
+                    changed.push((setting.property, value));
+                }
+                Ok(_) => (),
+                Err(err) => {
+                    self.status = Some(Err(format!("{}: {err}", setting.directive)));
+                    return;
+                }
+            }
+        }
+        if changed.is_empty() {
+            self.status = Some(Ok("Nothing to change.".to_owned()));
+            return;
+        }
+
+        let count = changed.len();
+        let result = Promise::spawn_async(systemd::set_resource_limits(
+            con.clone(),
+            unit.name.clone(),
+            self.runtime,
+            changed,
+        ))
+        .block_and_take();
+        // Re-read right away so the graphs show the new limits.
+        self.reload(con, unit);
+        self.status = Some(match result {
+            Ok(()) => Ok(format!("Changed {count} setting(s).")),
+            Err(err) => Err(err.to_string()),
+        });
+    }
+
+    fn sample(&mut self, con: &zbus::Connection, unit: &UnitData, interface: &'static str) {
+        self.last_sample = Some(Instant::now());
+        let usage = Promise::spawn_async(systemd::resource_usage(
+            con.clone(),
+            unit.object_path.clone(),
+            interface,
+        ))
+        .block_and_take();
+        if let (Ok(usage), Some(opened)) = (usage, self.opened) {
+            if self.samples.len() == MAX_SAMPLES {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(Sample {
+                at: opened.elapsed().as_secs_f64(),
+                usage,
+            });
+        }
+    }
+
+    fn limit(&self, directive: &str) -> Option<u64> {
+        let index = RESOURCE_SETTINGS
+            .iter()
+            .position(|s| s.directive == directive)?;
+        self.limits
+            .get(index)
+            .copied()
+            .filter(|value| *value != u64::MAX)
+    }
+
+    fn graphs(&self, ui: &mut Ui) {
+        let memory: PlotPoints = self
+            .samples
+            .iter()
+            .filter(|s| s.usage.memory != u64::MAX)
+            .map(|s| [s.at, s.usage.memory as f64 / (1 << 20) as f64])
+            .collect();
+        ui.label("Memory (MiB)");
+        Plot::new("resource_memory")
+            .height(96.0)
+            .legend(Legend::default())
+            .show(ui, |plot| {
+                plot.line(Line::new(memory).name("MemoryCurrent"));
+                for (directive, color) in
+                    [("MemoryHigh", Color32::GOLD), ("MemoryMax", Color32::RED)]
+                {
+                    if let Some(limit) = self.limit(directive) {
+                        plot.hline(
+                            HLine::new(limit as f64 / (1 << 20) as f64)
+                                .color(color)
+                                .name(directive),
+                        );
+                    }
+                }
+            });
+
+        let cpu: PlotPoints = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .filter(|(a, b)| a.usage.cpu_nsec != u64::MAX && b.usage.cpu_nsec >= a.usage.cpu_nsec)
+            .map(|(a, b)| {
+                let busy = (b.usage.cpu_nsec - a.usage.cpu_nsec) as f64 / 1e9;
+                [b.at, busy * 100.0 / (b.at - a.at)]
+            })
+            .collect();
+        ui.label("CPU (%)");
+        Plot::new("resource_cpu")
+            .height(96.0)
+            .legend(Legend::default())
+            .show(ui, |plot| {
+                plot.line(Line::new(cpu).name("CPU usage"));
+                if let Some(quota) = self.limit("CPUQuota") {
+                    plot.hline(
+                        HLine::new(quota as f64 / 1e4)
+                            .color(Color32::RED)
+                            .name("CPUQuota"),
+                    );
+                }
+            });
+
+        let tasks: PlotPoints = self
+            .samples
+            .iter()
+            .filter(|s| s.usage.tasks != u64::MAX)
+            .map(|s| [s.at, s.usage.tasks as f64])
+            .collect();
+        ui.label("Tasks");
+        Plot::new("resource_tasks")
+            .height(96.0)
+            .legend(Legend::default())
+            .show(ui, |plot| {
+                plot.line(Line::new(tasks).name("TasksCurrent"));
+                if let Some(max) = self.limit("TasksMax") {
+                    plot.hline(HLine::new(max as f64).color(Color32::RED).name("TasksMax"));
+                }
+            });
+    }
+}
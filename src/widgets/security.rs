@@ -0,0 +1,133 @@
+use egui::{Color32, Ui};
+use egui_extras::Column;
+use poll_promise::Promise;
+
+use crate::security::{self, Exposure};
+use crate::systemd::UnitData;
+
+use super::hardening::HardeningAssistant;
+
+pub fn score_color(score: f64) -> Color32 {
+    match score {
+        score if score < 2.0 => Color32::GREEN,
+        score if score < 5.0 => Color32::LIGHT_GREEN,
+        score if score < 7.0 => Color32::YELLOW,
+        score if score < 9.0 => Color32::GOLD,
+        _ => Color32::RED,
+    }
+}
+
+/// The exposure score of a unit, with what each check contributes to it.
+#[derive(Default)]
+pub struct SecurityPanel {
+    unit: Option<String>,
+    exposure: Option<zbus::Result<Exposure>>,
+}
+
+impl SecurityPanel {
+    /// Returns the hardening assistant once the user asks for it.
+    pub fn draw(
+        &mut self,
+        ui: &mut Ui,
+        con: &zbus::Connection,
+        unit: &UnitData,
+    ) -> Option<HardeningAssistant> {
+        let (rescore, harden) = ui
+            .horizontal(|ui| {
+                let rescore = ui.button("Rescore").clicked();
+                let harden = matches!(self.exposure, Some(Ok(_))) && ui.button("Harden…").clicked();
+                (rescore, harden)
+            })
+            .inner;
+        if rescore || self.unit.as_deref() != Some(unit.name.as_str()) {
+            self.unit = Some(unit.name.clone());
+            self.exposure = Some(
+                Promise::spawn_async(security::exposure(
+                    con.clone(),
+                    unit.object_path.clone(),
+                    unit.name.clone(),
+                ))
+                .block_and_take(),
+            );
+        }
+
+        match &self.exposure {
+            Some(Ok(exposure)) => breakdown(ui, exposure),
+            Some(Err(err)) => {
+                ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+            }
+            None => (),
+        }
+
+        match &self.exposure {
+            Some(Ok(exposure)) if harden => {
+                Some(HardeningAssistant::new(con, unit, exposure.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn breakdown(ui: &mut Ui, exposure: &Exposure) {
+    let score = exposure.score();
+    ui.horizontal(|ui| {
+        ui.label("Overall exposure:");
+        ui.colored_label(
+            score_color(score),
+            format!("{score:.1} {}", security::level(score)),
+        );
+    });
+
+    let total = exposure.total_weight();
+    let mut checks: Vec<_> = exposure.checks.iter().collect();
+    checks.sort_by(|a, b| b.exposure(total).total_cmp(&a.exposure(total)));
+
+    let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
+    egui_extras::TableBuilder::new(ui)
+        .striped(true)
+        .vscroll(false)
+        .column(Column::auto().at_least(16.0))
+        .column(Column::auto().at_least(160.0))
+        .column(Column::auto().at_least(96.0))
+        .column(Column::auto().at_least(48.0))
+        .column(Column::remainder())
+        .header(text_height * 1.5, |mut header| {
+            for title in ["", "directive", "value", "exposure", "description"] {
+                header.col(|ui| {
+                    ui.label(title);
+                });
+            }
+        })
+        .body(|body| {
+            body.rows(text_height * 1.5, checks.len(), |index, mut row| {
+                let check = checks[index];
+                row.col(|ui| match check.badness {
+                    Some(0) => {
+                        ui.colored_label(Color32::GREEN, "✔");
+                    }
+                    Some(_) => {
+                        ui.colored_label(Color32::RED, "✘");
+                    }
+                    None => {
+                        ui.weak("?");
+                    }
+                });
+                row.col(|ui| {
+                    ui.monospace(format!("{}=", check.directive));
+                });
+                row.col(|ui| {
+                    ui.style_mut().wrap = Some(false);
+                    ui.label(&check.value);
+                });
+                row.col(|ui| {
+                    if check.badness.is_some() {
+                        ui.label(format!("{:.2}", check.exposure(total)));
+                    }
+                });
+                row.col(|ui| {
+                    ui.style_mut().wrap = Some(false);
+                    ui.label(check.description);
+                });
+            });
+        });
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use crate::security;
 use crate::systemd;
 use crate::systemd::UnitData;
 use crate::widgets::units_table::units_table;
@@ -7,11 +10,19 @@ use egui::Ui;
 use poll_promise::Promise;
 
 use super::journal::JournalWindow;
+use super::lookup::Lookup;
+use crate::journal::JournalSource;
 
 pub struct Services {
     units_promise: Promise<zbus::Result<Vec<UnitData>>>,
+    /// Exposure scores by unit, computed once the units are listed.
+    exposures: Option<Promise<HashMap<String, f64>>>,
+    sort_by_exposure: bool,
     properties: PropertiesWindow,
     journal: JournalWindow,
+    lookup: Lookup,
+    /// The owner last looked up, when it isn't among the loaded units.
+    not_loaded: Option<String>,
     con: zbus::Connection,
 }
 
@@ -19,8 +30,12 @@ impl Services {
     pub fn new(con: zbus::Connection, options: OpenOptions) -> Self {
         Services {
             units_promise: Promise::spawn_async(systemd::list_units(con.clone())),
-            properties: PropertiesWindow::with_connection(con.clone()),
-            journal: JournalWindow::new(options),
+            exposures: None,
+            sort_by_exposure: false,
+            properties: PropertiesWindow::with_connection(con.clone(), options.clone()),
+            journal: JournalWindow::new(JournalSource::Local(options)),
+            lookup: Lookup::default(),
+            not_loaded: None,
             con,
         }
     }
@@ -31,6 +46,7 @@ impl Services {
 
     fn refresh(&mut self) {
         self.units_promise = Promise::spawn_async(systemd::list_units(self.con.clone()));
+        self.exposures = None;
     }
 
     pub fn draw(&mut self, ui: &mut Ui) {
@@ -42,11 +58,48 @@ impl Services {
         if ui.button("View Journal for All").clicked() {
             self.journal.open(None)
         }
+        if ui.button("Find Owner…").clicked() {
+            self.lookup.open();
+        }
+        let owner = self.lookup.draw(ui.ctx(), &self.con);
         if let Some(response) = self.units_promise.ready() {
             match response {
                 Ok(units) => {
                     ui.heading(format!("services: {}", units.len()));
-                    units_table(units, ui, &mut unit_index, &mut journal_index);
+                    let exposures = self.exposures.get_or_insert_with(|| {
+                        let units = units
+                            .iter()
+                            .map(|unit| (unit.name.clone(), unit.object_path.clone()))
+                            .collect();
+                        Promise::spawn_async(security::exposures(self.con.clone(), units))
+                    });
+                    units_table(
+                        units,
+                        exposures.ready(),
+                        &mut self.sort_by_exposure,
+                        ui,
+                        &mut unit_index,
+                        &mut journal_index,
+                    );
+
+                    if let Some(owner) = owner {
+                        self.not_loaded = None;
+                        match units.iter().position(|unit| unit.name == owner) {
+                            Some(index) => unit_index = Some(index),
+                            None => self.not_loaded = Some(owner),
+                        }
+                    }
+                    if let Some(owner) = &self.not_loaded {
+                        let dismissed = ui
+                            .horizontal(|ui| {
+                                ui.label(format!("{owner} isn't a loaded service"));
+                                ui.small_button("✖").clicked()
+                            })
+                            .inner;
+                        if dismissed {
+                            self.not_loaded = None;
+                        }
+                    }
 
                     if let Some(index) = unit_index {
                         self.properties.open(index);
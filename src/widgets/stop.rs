@@ -0,0 +1,132 @@
+use egui::{Color32, Context};
+use poll_promise::Promise;
+use zbus_systemd::systemd1::UnitProxy;
+use zvariant::OwnedObjectPath;
+
+use crate::systemd::{self, ActiveState, UnitData, ENQUEUE_JOB_SINCE};
+
+use super::unitdata::active_state_to_color;
+
+/// Shows what else goes down with a unit before stopping it.
+pub struct StopDialog {
+    unit: String,
+    path: OwnedObjectPath,
+    impact: Result<Vec<(String, ActiveState)>, String>,
+    /// The `(unit, job type)` of the jobs the manager reported queuing.
+    queued: Option<Vec<(String, String)>>,
+    error: Option<String>,
+}
+
+impl StopDialog {
+    pub fn new(con: &zbus::Connection, unit: &UnitData) -> Self {
+        let impact = Promise::spawn_async(systemd::stop_impact(con.clone(), unit.name.clone()))
+            .block_and_take()
+            .map_err(|err| err.to_string());
+        Self {
+            unit: unit.name.clone(),
+            path: unit.object_path.clone(),
+            impact,
+            queued: None,
+            error: None,
+        }
+    }
+
+    /// Returns false once the dialog is done with, either stopped or closed.
+    pub fn draw(&mut self, ctx: &Context, con: &zbus::Connection, version: u32) -> bool {
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new(format!("Stop {}", self.unit))
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if let Some(queued) = &self.queued {
+                    ui.label("The manager queued these jobs as well:");
+                    if queued.is_empty() {
+                        ui.weak("none");
+                    }
+                    for (unit, job_type) in queued {
+                        ui.label(format!("{job_type} {unit}"));
+                    }
+                    done = ui.button("Close").clicked();
+                    return;
+                }
+
+                match &self.impact {
+                    Ok(impact) if impact.is_empty() => {
+                        ui.label(format!("Nothing else depends on {}.", self.unit));
+                    }
+                    Ok(impact) => {
+                        ui.label(format!("Stopping {} also stops:", self.unit));
+                        egui::ScrollArea::vertical()
+                            .max_height(256.0)
+                            .show(ui, |ui| {
+                                egui::Grid::new("stop_impact").striped(true).show(ui, |ui| {
+                                    for (unit, state) in impact {
+                                        // Units that are down already aren't affected.
+                                        if state.can_start() {
+                                            ui.weak(unit);
+                                        } else {
+                                            ui.label(unit);
+                                        }
+                                        ui.colored_label(
+                                            active_state_to_color(state),
+                                            state.to_string(),
+                                        );
+                                        ui.end_row();
+                                    }
+                                });
+                            });
+                    }
+                    Err(err) => {
+                        ui.colored_label(
+                            Color32::DEBUG_COLOR,
+                            format!("ERROR: can't tell what else stops: {err}"),
+                        );
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let stop = egui::Button::new("Stop").fill(Color32::DARK_RED);
+                    if ui.add(stop).clicked() {
+                        match self.stop(con, version) {
+                            Ok(Some(queued)) => self.queued = Some(queued),
+                            Ok(None) => done = true,
+                            Err(err) => self.error = Some(err.to_string()),
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        done = true;
+                    }
+                });
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                }
+            });
+        open && !done
+    }
+
+    /// Stops the unit, returning the other queued jobs when the manager is
+    /// new enough to report them.
+    fn stop(
+        &self,
+        con: &zbus::Connection,
+        version: u32,
+    ) -> zbus::Result<Option<Vec<(String, String)>>> {
+        if version >= ENQUEUE_JOB_SINCE {
+            return Promise::spawn_async(systemd::enqueue_stop(con.clone(), self.unit.clone()))
+                .block_and_take()
+                .map(Some);
+        }
+        let con = con.clone();
+        let path = self.path.clone();
+        Promise::spawn_async(async move {
+            UnitProxy::new(&con, path)
+                .await?
+                .stop("replace".to_owned())
+                .await
+        })
+        .block_and_take()
+        .map(|_| None)
+    }
+}
@@ -1,17 +1,31 @@
+use std::time::{Duration, Instant};
+
 use egui::Ui;
 use poll_promise::Promise;
 use systemd::journal::OpenOptions;
 
 use crate::error::Error;
+use crate::systemd::{self as manager, SystemHealth};
 
+use super::boot::BootAnalysis;
+use super::failed::{Bus, FailedUnits};
+use super::unitdata::system_state_to_color;
 use super::Services;
 
+const HEALTH_INTERVAL: Duration = Duration::from_secs(2);
+
 pub struct Overview {
     system_bus: zbus::Connection,
     session_bus: zbus::Connection,
 
     system_services: Services,
     user_services: Services,
+    failed: FailedUnits,
+    boot: BootAnalysis,
+
+    /// The system, then the user manager's health.
+    health: [Option<zbus::Result<SystemHealth>>; 2],
+    health_refreshed: Option<Instant>,
 
     tab: String,
 }
@@ -24,11 +38,18 @@ impl Overview {
         Ok(Overview {
             system_bus: system_bus.clone(),
             session_bus: session_bus.clone(),
-            system_services: Services::new(system_bus, OpenOptions::default().system(true).clone()),
+            system_services: Services::new(
+                system_bus.clone(),
+                OpenOptions::default().system(true).clone(),
+            ),
             user_services: Services::new(
                 session_bus,
                 OpenOptions::default().current_user(true).clone(),
             ),
+            failed: FailedUnits::default(),
+            boot: BootAnalysis::new(system_bus.clone()),
+            health: [None, None],
+            health_refreshed: None,
             tab: "system".to_string(),
         })
     }
@@ -36,11 +57,21 @@ impl Overview {
     pub fn draw(&mut self, ui: &mut Ui) {
         let system_tab = "system".to_string();
         let user_tab = "user".to_string();
+        let failed_tab = "failed".to_string();
+        let boot_tab = "boot".to_string();
+
+        self.draw_health(ui);
 
         let sys_services_radio = ui.radio_value(&mut self.tab, system_tab, "System Services");
         let user_serices_radio = ui.radio_value(&mut self.tab, user_tab, "User Services");
+        let failed_radio = ui.radio_value(&mut self.tab, failed_tab, "Failed");
+        let boot_radio = ui.radio_value(&mut self.tab, boot_tab, "Boot");
 
-        if sys_services_radio.clicked() || user_serices_radio.clicked() {
+        if sys_services_radio.clicked()
+            || user_serices_radio.clicked()
+            || failed_radio.clicked()
+            || boot_radio.clicked()
+        {
             self.system_services.close_properties();
             self.user_services.close_properties();
         }
@@ -50,7 +81,66 @@ impl Overview {
         match self.tab.as_str() {
             "system" => self.system_services.draw(ui),
             "user" => self.user_services.draw(ui),
+            "failed" => self.failed.draw(
+                ui,
+                &[
+                    Bus {
+                        name: "system",
+                        con: &self.system_bus,
+                    },
+                    Bus {
+                        name: "user",
+                        con: &self.session_bus,
+                    },
+                ],
+            ),
+            "boot" => self.boot.draw(ui),
             _ => todo!(),
         }
     }
+
+    /// Shows the state of both managers, with a shortcut to the failed units.
+    fn draw_health(&mut self, ui: &mut Ui) {
+        if !matches!(self.health_refreshed, Some(at) if at.elapsed() < HEALTH_INTERVAL) {
+            self.health_refreshed = Some(Instant::now());
+            for (health, con) in self
+                .health
+                .iter_mut()
+                .zip([&self.system_bus, &self.session_bus])
+            {
+                *health = Some(
+                    Promise::spawn_async(manager::system_health(con.clone())).block_and_take(),
+                );
+            }
+        }
+        ui.ctx().request_repaint_after(HEALTH_INTERVAL);
+
+        let mut show_failed = false;
+        ui.horizontal(|ui| {
+            for (name, health) in ["System", "User"].into_iter().zip(&self.health) {
+                ui.label(format!("{name}:"));
+                match health {
+                    Some(Ok(health)) => {
+                        ui.colored_label(system_state_to_color(&health.state), &health.state);
+                        if health.failed_units > 0 {
+                            let failed = format!("{} failed", health.failed_units);
+                            show_failed |= ui.link(failed).clicked();
+                        }
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(egui::Color32::DEBUG_COLOR, format!("ERROR: {err}"));
+                    }
+                    None => {
+                        ui.spinner();
+                    }
+                }
+                ui.separator();
+            }
+        });
+        if show_failed {
+            self.tab = "failed".to_string();
+            self.system_services.close_properties();
+            self.user_services.close_properties();
+        }
+    }
 }
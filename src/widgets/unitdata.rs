@@ -1,7 +1,7 @@
-use crate::systemd::{self, ActiveState, LoadState, UnitFilePreset, UnitFileState};
+use crate::systemd::{self, ActiveState, FreezerState, LoadState, UnitFilePreset, UnitFileState};
 use egui::{Color32, Widget};
 
-pub fn active_state_to_color(state: ActiveState) -> Color32 {
+pub fn active_state_to_color(state: &ActiveState) -> Color32 {
     match state {
         systemd::ActiveState::Active => Color32::GREEN,
         systemd::ActiveState::Failed => Color32::RED,
@@ -9,6 +9,9 @@ pub fn active_state_to_color(state: ActiveState) -> Color32 {
         systemd::ActiveState::Reloading => Color32::YELLOW,
         systemd::ActiveState::Activating => Color32::YELLOW,
         systemd::ActiveState::Deactivating => Color32::YELLOW,
+        systemd::ActiveState::Maintenance => Color32::GOLD,
+        systemd::ActiveState::Refreshing => Color32::YELLOW,
+        systemd::ActiveState::Unknown(_) => Color32::GRAY,
     }
 }
 
@@ -20,19 +23,66 @@ pub fn load_state_to_color(state: LoadState) -> Color32 {
     }
 }
 
-pub type ActiveStateLabel = ActiveState;
+pub fn freezer_state_to_color(state: &FreezerState) -> Color32 {
+    match state {
+        FreezerState::Running => Color32::GREEN,
+        FreezerState::Frozen | FreezerState::FrozenByParent => Color32::LIGHT_BLUE,
+        FreezerState::Freezing | FreezerState::FreezingByParent | FreezerState::Thawing => {
+            Color32::YELLOW
+        }
+        FreezerState::Unknown(_) => Color32::GRAY,
+    }
+}
+
+/// Colors a `Manager.SystemState`, e.g. `running` or `degraded`.
+pub fn system_state_to_color(state: &str) -> Color32 {
+    match state {
+        "running" => Color32::GREEN,
+        "degraded" => Color32::RED,
+        "maintenance" | "offline" => Color32::GOLD,
+        "initializing" | "starting" | "stopping" => Color32::YELLOW,
+        _ => Color32::GRAY,
+    }
+}
+
+pub struct ActiveStateLabel {
+    pub state: ActiveState,
+    /// Only known when the manager supports freezing.
+    pub freezer: Option<FreezerState>,
+}
+
+impl From<ActiveState> for ActiveStateLabel {
+    fn from(state: ActiveState) -> Self {
+        Self {
+            state,
+            freezer: None,
+        }
+    }
+}
 
 impl Widget for ActiveStateLabel {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
         ui.horizontal_wrapped(|ui| {
             ui.label("Active:");
-            ui.colored_label(active_state_to_color(self), self.to_string());
-            match self {
-                ActiveState::Activating | ActiveState::Reloading | ActiveState::Deactivating => {
+            ui.colored_label(active_state_to_color(&self.state), self.state.to_string());
+            match self.state {
+                ActiveState::Activating
+                | ActiveState::Reloading
+                | ActiveState::Deactivating
+                | ActiveState::Refreshing => {
                     ui.spinner();
                 }
                 _ => (),
             };
+            if let Some(freezer) = self.freezer.filter(|f| *f != FreezerState::Running) {
+                ui.colored_label(freezer_state_to_color(&freezer), format!("({freezer})"));
+                if matches!(
+                    freezer,
+                    FreezerState::Freezing | FreezerState::FreezingByParent | FreezerState::Thawing
+                ) {
+                    ui.spinner();
+                }
+            }
         })
         .response
     }
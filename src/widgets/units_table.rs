@@ -1,16 +1,31 @@
-use egui::Ui;
+use std::collections::HashMap;
+
+use egui::{RichText, SelectableLabel, Ui};
 use egui_extras::Column;
 
 use crate::systemd::UnitData;
 
+use super::security::score_color;
 use super::unitdata::active_state_to_color;
 
 pub fn units_table(
     units: &Vec<UnitData>,
+    exposures: Option<&HashMap<String, f64>>,
+    sort_by_exposure: &mut bool,
     ui: &mut Ui,
     open_index: &mut Option<usize>,
     journal_index: &mut Option<usize>,
 ) {
+    let exposure = |index: usize| exposures.and_then(|scores| scores.get(&units[index].name));
+    let mut order: Vec<usize> = (0..units.len()).collect();
+    if *sort_by_exposure {
+        // Most exposed first, the ones not scored (yet) last.
+        order.sort_by(|a, b| {
+            let score = |index| exposure(index).copied().unwrap_or(f64::NEG_INFINITY);
+            score(*b).total_cmp(&score(*a))
+        });
+    }
+
     egui::ScrollArea::both()
         .auto_shrink([false, false])
         .show(ui, |ui| {
@@ -23,6 +38,7 @@ pub fn units_table(
                 .column(Column::auto().at_least(256.0))
                 .column(Column::remainder().at_least(64.0).at_most(64.0))
                 .column(Column::remainder().at_least(64.0).at_most(64.0))
+                .column(Column::remainder().at_least(80.0).at_most(80.0))
                 .column(Column::remainder()) //.column(Column::auto().at_least(256.0))
                 .header(text_height * 2.0, |mut header| {
                     header.col(|ui| {
@@ -46,6 +62,18 @@ pub fn units_table(
                             ui.heading("state");
                         });
                     });
+                    header.col(|ui| {
+                        ui.vertical_centered_justified(|ui| {
+                            let label = RichText::new("exposure").heading();
+                            if ui
+                                .add(SelectableLabel::new(*sort_by_exposure, label))
+                                .on_hover_text("Sort by exposure")
+                                .clicked()
+                            {
+                                *sort_by_exposure = !*sort_by_exposure;
+                            }
+                        });
+                    });
                     header.col(|ui| {
                         ui.horizontal_wrapped(|ui| {
                             ui.add_space(4.0);
@@ -54,7 +82,8 @@ pub fn units_table(
                     });
                 })
                 .body(|b| {
-                    b.rows(text_height * 2.0, units.len(), |index, mut row| {
+                    b.rows(text_height * 2.0, units.len(), |row_index, mut row| {
+                        let index = order[row_index];
                         row.col(|ui| {
                             ui.horizontal_wrapped(|ui| {
                                 if ui.button("Properties").clicked() {
@@ -77,7 +106,7 @@ pub fn units_table(
                         row.col(|ui| {
                             ui.vertical_centered_justified(|ui| {
                                 ui.colored_label(
-                                    active_state_to_color(units[index].active_status),
+                                    active_state_to_color(&units[index].active_status),
                                     units[index].active_status.to_string(),
                                 );
                             });
@@ -87,6 +116,19 @@ pub fn units_table(
                                 ui.label(&units[index].load_status.to_string());
                             });
                         });
+                        row.col(|ui| {
+                            ui.vertical_centered_justified(|ui| match exposure(index) {
+                                Some(score) => {
+                                    ui.colored_label(score_color(*score), format!("{score:.1}"));
+                                }
+                                None if exposures.is_none() => {
+                                    ui.spinner();
+                                }
+                                None => {
+                                    ui.weak("-");
+                                }
+                            });
+                        });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
                                 ui.add_space(4.0);